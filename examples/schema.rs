@@ -9,8 +9,14 @@ use cw721::{
 };
 use cw721_base::MinterResponse;
 use whoami::msg::{
-    AddressOfResponse, ContractInfoResponse, GetParentIdResponse, GetPathResponse,
-    IsContractResponse, ListUserInfoResponse, MintingFeesResponse, PrimaryAliasResponse,
+    AddressOfResponse, AddressRecord, AddressesResponse, AllPrimaryAliasesResponse,
+    ContractInfoResponse, DepositResponse, DerivedAddressesResponse, Fuses, GetParentIdResponse,
+    GetPathResponse, IsContractResponse, ListUserInfoResponse, MetadataRevisionResponse,
+    MintAllowanceResponse, MintPriceResponse, MintingFeesResponse, NameCategoriesResponse,
+    PathLimits, PathMintPriceResponse, PrimaryAliasPolicy, PrimaryAliasResponse,
+    PrimaryAliasesResponse, ProfileResponse, RateLimits, ResolvePathResponse,
+    ResolvedMetadataResponse, TextRecordLimits, TextRecordResponse, TextRecordsResponse,
+    TreeResponse,
 };
 use whoami::{ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg};
 
@@ -41,10 +47,32 @@ fn main() {
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
     export_schema(&schema_for!(PrimaryAliasResponse), &out_dir);
+    export_schema(&schema_for!(PrimaryAliasPolicy), &out_dir);
+    export_schema(&schema_for!(PrimaryAliasesResponse), &out_dir);
+    export_schema(&schema_for!(ProfileResponse), &out_dir);
+    export_schema(&schema_for!(AllPrimaryAliasesResponse), &out_dir);
+    export_schema(&schema_for!(AddressesResponse), &out_dir);
+    export_schema(&schema_for!(TextRecordLimits), &out_dir);
+    export_schema(&schema_for!(TextRecordResponse), &out_dir);
+    export_schema(&schema_for!(TextRecordsResponse), &out_dir);
+    export_schema(&schema_for!(AddressRecord), &out_dir);
+    export_schema(&schema_for!(DerivedAddressesResponse), &out_dir);
+    export_schema(&schema_for!(MetadataRevisionResponse), &out_dir);
     export_schema(&schema_for!(MintingFeesResponse), &out_dir);
     export_schema(&schema_for!(IsContractResponse), &out_dir);
     export_schema(&schema_for!(AddressOfResponse), &out_dir);
     export_schema(&schema_for!(GetParentIdResponse), &out_dir);
     export_schema(&schema_for!(GetPathResponse), &out_dir);
     export_schema(&schema_for!(ListUserInfoResponse), &out_dir);
+    export_schema(&schema_for!(ResolvePathResponse), &out_dir);
+    export_schema(&schema_for!(TreeResponse), &out_dir);
+    export_schema(&schema_for!(PathLimits), &out_dir);
+    export_schema(&schema_for!(Fuses), &out_dir);
+    export_schema(&schema_for!(PathMintPriceResponse), &out_dir);
+    export_schema(&schema_for!(MintPriceResponse), &out_dir);
+    export_schema(&schema_for!(NameCategoriesResponse), &out_dir);
+    export_schema(&schema_for!(DepositResponse), &out_dir);
+    export_schema(&schema_for!(ResolvedMetadataResponse), &out_dir);
+    export_schema(&schema_for!(RateLimits), &out_dir);
+    export_schema(&schema_for!(MintAllowanceResponse), &out_dir);
 }
@@ -0,0 +1,124 @@
+// a minimal bech32 (BIP-173) implementation
+// so addresses for other chains can be checked without pulling in a crate
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+// cosmos addresses can be longer than the 90 characters of the original spec
+const MAX_LENGTH: usize = 1023;
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(*value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|b| b & 0x1f));
+    expanded
+}
+
+// regroups bits, e.g. from 5 bit bech32 words into bytes
+// returns None if the padding isn't valid
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut converted = vec![];
+    for value in data {
+        acc = (acc << from) | u32::from(*value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            converted.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return None;
+    }
+    Some(converted)
+}
+
+// a human readable part is 1 to 83 printable ascii characters
+// only lowercase is accepted, as that is what addresses use
+pub fn hrp_is_valid(hrp: &str) -> bool {
+    !hrp.is_empty()
+        && hrp.len() <= 83
+        && hrp
+            .bytes()
+            .all(|b| (33..=126).contains(&b) && !b.is_ascii_uppercase())
+}
+
+// splits an address into its human readable part and data bytes
+// returns None if it isn't valid bech32
+pub fn decode(address: &str) -> Option<(String, Vec<u8>)> {
+    if address.len() > MAX_LENGTH {
+        return None;
+    }
+
+    // mixed case is never valid, but all uppercase is
+    let has_lower = address.bytes().any(|b| b.is_ascii_lowercase());
+    let has_upper = address.bytes().any(|b| b.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return None;
+    }
+    let address = address.to_ascii_lowercase();
+
+    let separator = address.rfind('1')?;
+    let (hrp, data) = (&address[..separator], &address[separator + 1..]);
+    if !hrp_is_valid(hrp) || data.len() < 6 {
+        return None;
+    }
+
+    let words = data
+        .bytes()
+        .map(|b| CHARSET.iter().position(|c| *c == b).map(|p| p as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut values = expand_hrp(hrp);
+    values.extend(&words);
+    if polymod(&values) != 1 {
+        return None;
+    }
+
+    let bytes = convert_bits(&words[..words.len() - 6], 5, 8, false)?;
+    Some((hrp.to_string(), bytes))
+}
+
+// the inverse of decode
+// hrp must already be valid
+pub fn encode(hrp: &str, data: &[u8]) -> String {
+    let mut words = convert_bits(data, 8, 5, true).unwrap_or_default();
+
+    let mut values = expand_hrp(hrp);
+    values.extend(&words);
+    values.extend(&[0u8; 6]);
+    let checksum = polymod(&values) ^ 1;
+    words.extend((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 0x1f) as u8));
+
+    let mut encoded = String::with_capacity(hrp.len() + 1 + words.len());
+    encoded.push_str(hrp);
+    encoded.push('1');
+    encoded.extend(words.iter().map(|w| CHARSET[*w as usize] as char));
+    encoded
+}
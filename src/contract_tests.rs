@@ -4,23 +4,50 @@ mod tests {
     use crate::entry;
 
     use crate::utils::{
-        is_path, namespace_in_path, pgp_pubkey_format_is_valid, remove_namespace_from_path,
-        validate_path_characters, validate_username_characters,
+        bech32_address_is_valid, is_numeric_name, is_palindrome_name, is_path,
+        is_repeated_character_name, namespace_in_path, validate_path_characters,
+        validate_username_characters,
+    };
+
+    use crate::validation::{
+        discord_handle_is_valid, email_is_valid, keybase_handle_is_valid,
+        pgp_pubkey_format_is_valid, telegram_handle_is_valid, twitter_handle_is_valid,
+        url_is_valid,
     };
 
     use crate::error::ContractError;
 
+    use crate::bech32;
+    use crate::execute::{
+        CONTRACT_VERSION, MAX_TEXT_RECORDS, MAX_TEXT_RECORD_KEY_LENGTH,
+        MAX_TEXT_RECORD_VALUE_LENGTH, MIGRATION_BATCH_SIZE, PATH_BURN_BATCH_SIZE,
+        PATH_MOVE_BATCH_SIZE,
+    };
     use crate::msg::{
-        AddressOfResponse, ContractInfoResponse, ExecuteMsg, Extension, GetParentIdResponse,
-        GetPathResponse, InstantiateMsg, IsContractResponse, Metadata, MintMsg,
-        PrimaryAliasResponse, QueryMsg, SurchargeInfo, UpdateMetadataMsg, UpdateMintingFeesMsg,
+        AddressOfResponse, AddressRecord, AddressSource, AddressesResponse,
+        AllPrimaryAliasesResponse, BondingCurve, Chain, ContractInfo, ContractInfoResponse,
+        DepositResponse, DerivedAddressesResponse, ExecuteMsg, Extension, FieldUpdate, Fuses,
+        GetParentIdResponse, GetPathResponse, InstantiateMsg, IsContractResponse, Metadata,
+        MetadataFieldSource, MetadataRevisionResponse, MigrateMsg, MintAllowanceResponse, MintMsg,
+        MintPriceResponse, NameCategoriesResponse, NameCategory, NameCategoryPricing,
+        PatchMetadataMsg, PathLimits, PathMintPriceResponse, PrimaryAliasEntry, PrimaryAliasPolicy,
+        PrimaryAliasRecord, PrimaryAliasResponse, PrimaryAliasSource, PrimaryAliasesResponse,
+        ProfileResponse, QueryMsg, RateLimits, RegistrationModel, ResolvePathResponse,
+        ResolvedMetadataResponse, SurchargeInfo, TextRecordLimits, TextRecordResponse,
+        TextRecordsResponse, TreeNode, TreeResponse, UpdateMetadataMsg, UpdateMintingFeesMsg,
         WhoamiNftInfoResponse,
     };
+    use crate::query::MAX_ALIAS_BATCH;
+    use crate::state::{
+        ContractReverseRecord, CONTRACT_REVERSE_RECORDS, LEGACY_CONTRACT_INFO, PARENT_INDEX, PATHS,
+        PATH_COUNTS, PRIMARY_ALIASES,
+    };
     use crate::Cw721MetadataContract;
     use cosmwasm_std::{
-        coins, from_binary, to_binary, BankMsg, CosmosMsg, Decimal, DepsMut, Response, StdError,
-        Uint128,
+        coins, from_binary, from_slice, to_binary, Addr, Api, BankMsg, CosmosMsg, Decimal, Deps,
+        DepsMut, Order, Response, StdError, StdResult, Uint128,
     };
+    use cw721_base::state::TokenInfo;
     use cw721_base::MinterResponse;
 
     use cw721::{Cw721Query, NftInfoResponse, OwnerOfResponse, TokensResponse};
@@ -107,104 +134,118 @@ XGo8Un24WP40IT78XjKO
         assert_eq!(fourteenth_check, false);
     }
 
+    #[test]
+    fn bech32_validator() {
+        // test vectors from BIP-173
+        assert!(bech32_address_is_valid("A12UEL5L", "a"));
+        assert!(bech32_address_is_valid(
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "abcdef"
+        ));
+        assert!(bech32_address_is_valid(
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu",
+            "cosmos"
+        ));
+
+        // wrong prefix
+        assert!(!bech32_address_is_valid(
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu",
+            "osmo"
+        ));
+        // bad checksum
+        assert!(!bech32_address_is_valid(
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xv",
+            "cosmos"
+        ));
+        // mixed case
+        assert!(!bech32_address_is_valid(
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5Lzv7xu",
+            "cosmos"
+        ));
+        // no separator, and a character outside the charset
+        assert!(!bech32_address_is_valid("pzry9x0s0muk", "pzry"));
+        assert!(!bech32_address_is_valid("x1b4n0q5v", "x"));
+    }
+
+    #[test]
+    fn bech32_encoder() {
+        let address_bytes: Vec<u8> = (1..=20).collect();
+        assert_eq!(
+            bech32::encode("cosmos", &address_bytes),
+            "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu"
+        );
+        assert_eq!(
+            bech32::encode("osmo", &address_bytes),
+            "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw"
+        );
+
+        // and back again
+        let (hrp, decoded) = bech32::decode("osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw").unwrap();
+        assert_eq!(hrp, "osmo");
+        assert_eq!(decoded, address_bytes);
+    }
+
     #[test]
     fn path_validator() {
-        let first_check = validate_path_characters("jeffvader", "death-star-employees");
+        let first_check = validate_path_characters("jeffvader");
         assert_eq!(first_check, true);
 
-        let second_check =
-            validate_path_characters("jeffvader-notable-works", "death-star-employees");
+        let second_check = validate_path_characters("jeffvader-notable-works");
         assert_eq!(second_check, true);
 
         // if this were let through it would really screw things up
-        let third_check = validate_path_characters("jeff::vader", "death-star-employees");
+        let third_check = validate_path_characters("jeff::vader");
         assert_eq!(third_check, false);
 
-        let fourth_check = validate_path_characters("jeff-vader", "death-star-employees");
+        let fourth_check = validate_path_characters("jeff-vader");
         assert_eq!(fourth_check, true);
 
-        let fifth_check = validate_path_characters("jeff_vader", "death-star-employees");
+        let fifth_check = validate_path_characters("jeff_vader");
         assert_eq!(fifth_check, true);
 
         // no two special chars together
-        let sixth_check =
-            validate_path_characters("_jeff_vader/_past_employment", "death-star-employees");
+        let sixth_check = validate_path_characters("_jeff_vader/_past_employment");
         assert_eq!(sixth_check, false);
 
         // no leading, as it will result in same error case
-        let seventh_check = validate_path_characters("-jeff_vader", "death-star-employees");
+        let seventh_check = validate_path_characters("-jeff_vader");
         assert_eq!(seventh_check, false);
 
-        let eighth_check = validate_path_characters(
-            "jeffvader/past-construction-projects//death-star-one",
-            "death-star-employees",
-        );
+        let eighth_check =
+            validate_path_characters("jeffvader/past-construction-projects//death-star-one");
         assert_eq!(eighth_check, false);
 
-        let ninth_check = validate_path_characters("j3ffv4d3r", "death-star-employees");
+        let ninth_check = validate_path_characters("j3ffv4d3r");
         assert_eq!(ninth_check, true);
 
-        let tenth_check = validate_path_characters("j3ff_v4d3r", "death-star-employees");
+        let tenth_check = validate_path_characters("j3ff_v4d3r");
         assert_eq!(tenth_check, true);
 
-        let eleventh_check = validate_path_characters("j3ff__v4d3r", "death-star-employees");
+        let eleventh_check = validate_path_characters("j3ff__v4d3r");
         assert_eq!(eleventh_check, false);
 
-        let twelfth_check = validate_path_characters("jeff_-vader", "death-star-employees");
+        let twelfth_check = validate_path_characters("jeff_-vader");
         assert_eq!(twelfth_check, false);
 
         // strictly speaking these are invalid
         // but we should normalize before we even hit these
-        let thirteenth_check = validate_path_characters("JeffVader", "death-star-employees");
+        let thirteenth_check = validate_path_characters("JeffVader");
         assert_eq!(thirteenth_check, false);
 
         // no trailing
-        let fourteenth_check = validate_path_characters("jeffvader-", "death-star-employees");
+        let fourteenth_check = validate_path_characters("jeffvader-");
         assert_eq!(fourteenth_check, false);
 
-        let fifteenth_check = validate_path_characters(
-            "jeff/vader/trying/to/screw/up/parsing/",
-            "death-star-employees",
-        );
+        let fifteenth_check = validate_path_characters("jeff/vader/trying/to/screw/up/parsing/");
         assert_eq!(fifteenth_check, false);
 
-        // just like Just A Minute
-        // there will be no - well
-        // maybe deviation and hesitation
-        // but no repetition
-        let sixteenth_check = validate_path_characters("jeff-vader-trying-his-best", "jeff-vader");
-        assert_eq!(sixteenth_check, false);
-
-        let seventeenth_check =
-            validate_path_characters("trying-his-best-it-is-jeff-vader", "jeff-vader");
-        assert_eq!(seventeenth_check, false);
-    }
-
-    // reminder: a token_id (i.e. path) can only ever be namespaced under its parent
-    // so ids like jeff/vader::path/goes/here are not possible
-    // even if you _might_ see that as a fully-qualified path iif they own jeff & vader
-    // and have set jeff as the parent of vader. _phew_
-    #[test]
-    fn remove_namespace_from_path_test() {
-        // token id called is employment
-        let first_check = remove_namespace_from_path("jeffvader::employment", "jeffvader");
-        assert_eq!(first_check, "::employment");
-
-        // token id called is notable-works
-        let second_check = remove_namespace_from_path(
-            "jeffvader::notable-works/star-wars/a-new-hope",
-            "jeffvader",
-        );
-        assert_eq!(second_check, "::notable-works/star-wars/a-new-hope");
-
-        // token id called is employment
-        // should be no-op
-        let third_check = remove_namespace_from_path("employment", "vader");
-        assert_eq!(third_check, "employment");
+        // paths are stored as segments, so repeating
+        // the parent inside a segment is fine
+        let sixteenth_check = validate_path_characters("jeff-vader-trying-his-best");
+        assert_eq!(sixteenth_check, true);
 
-        let fourth_check =
-            remove_namespace_from_path("jeffvader::employment/death-star-1", "jeffvader");
-        assert_eq!(fourth_check, "::employment/death-star-1");
+        let seventeenth_check = validate_path_characters("trying-his-best-it-is-jeff-vader");
+        assert_eq!(seventeenth_check, true);
     }
 
     #[test]
@@ -249,6 +290,20 @@ XGo8Un24WP40IT78XjKO
 
         let fifth_check = namespace_in_path("jeffvader::employment/death-star-1", "yoda");
         assert_eq!(fifth_check, false);
+
+        // the namespace is never treated as a pattern
+        let sixth_check = namespace_in_path("jeffvader::employment", "jeff.*");
+        assert_eq!(sixth_check, false);
+
+        let seventh_check = namespace_in_path(
+            "jeffvader::employment::death-star-1",
+            "jeffvader::employment",
+        );
+        assert_eq!(seventh_check, true);
+
+        // a path is not in its own namespace
+        let eighth_check = namespace_in_path("jeffvader::employment", "jeffvader::employment");
+        assert_eq!(eighth_check, false);
     }
 
     const CREATOR: &str = "creator";
@@ -269,6 +324,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: String::from(MINTER),
             username_length_cap: None,
+            registration_model: None,
         };
         let info = mock_info("creator", &[]);
         let res = entry::instantiate(deps, mock_env(), info, msg).unwrap();
@@ -294,6 +350,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address,
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -305,6 +362,8 @@ XGo8Un24WP40IT78XjKO
             burn_percentage: None,
             token_cap: None,
             short_name_surcharge: None,
+            path_mint_fee: None,
+            bonding_curve: None,
         };
         let exec_msg = ExecuteMsg::UpdateMintingFees(mint_msg);
         entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
@@ -323,6 +382,11 @@ XGo8Un24WP40IT78XjKO
             base_mint_fee: Some(Uint128::new(1000000)),
             burn_percentage: None,
             short_name_surcharge: None,
+            path_mint_fee: None,
+            bonding_curve: None,
+            base_token_count: 0,
+            current_base_mint_fee: Some(Uint128::new(1000000)),
+            registration_model: RegistrationModel::Fee,
         };
 
         assert_eq!(contract_query_res, expected_res);
@@ -346,6 +410,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address,
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -360,6 +425,8 @@ XGo8Un24WP40IT78XjKO
                 surcharge_max_characters: 5,
                 surcharge_fee: Uint128::new(2000000),
             }),
+            path_mint_fee: Some(Uint128::new(100000)),
+            bonding_curve: None,
         };
         let exec_msg = ExecuteMsg::UpdateMintingFees(mint_msg);
         entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
@@ -381,6 +448,11 @@ XGo8Un24WP40IT78XjKO
                 surcharge_max_characters: 5,
                 surcharge_fee: Uint128::new(2000000),
             }),
+            path_mint_fee: Some(Uint128::new(100000)),
+            bonding_curve: None,
+            base_token_count: 0,
+            current_base_mint_fee: Some(Uint128::new(1000000)),
+            registration_model: RegistrationModel::Fee,
         };
 
         assert_eq!(contract_query_res, expected_res);
@@ -408,6 +480,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -487,6 +560,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address.clone(),
             username_length_cap: Some(22),
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -595,6 +669,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -689,6 +764,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: String::from(MINTER),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
@@ -800,7 +876,7 @@ XGo8Un24WP40IT78XjKO
         let token_uri = "https://example.com/jeff-vader".to_string();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -869,7 +945,7 @@ XGo8Un24WP40IT78XjKO
         let john_token_uri = "https://example.com/jeff-vader".to_string();
 
         let john_q_rando_meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -918,7 +994,7 @@ XGo8Un24WP40IT78XjKO
         );
 
         let meta2 = Metadata {
-            twitter_id: Some(String::from("@jeff-vader-alt")),
+            twitter_id: Some(String::from("@jeff_vader_alt")),
             ..Metadata::default()
         };
 
@@ -1053,6 +1129,7 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
@@ -1061,7 +1138,7 @@ XGo8Un24WP40IT78XjKO
         let token_uri = "https://example.com/jeff-vader".to_string();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -1443,6 +1520,7 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
@@ -1453,7 +1531,7 @@ XGo8Un24WP40IT78XjKO
         let lordvader_id = "lordvader".to_string();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -1750,6 +1828,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -1763,7 +1842,7 @@ XGo8Un24WP40IT78XjKO
         let token_id = "jeffisthebest".to_string();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -1856,6 +1935,7 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -1870,7 +1950,7 @@ XGo8Un24WP40IT78XjKO
 
         // CHECK: short username is caught
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -1997,6 +2077,7 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -2011,7 +2092,7 @@ XGo8Un24WP40IT78XjKO
 
         // CHECK: short username is caught
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2104,6 +2185,7 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(
             deps.as_mut(),
@@ -2118,7 +2200,7 @@ XGo8Un24WP40IT78XjKO
 
         // CHECK: short username is caught
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2181,7 +2263,7 @@ XGo8Un24WP40IT78XjKO
         let jeff_address = String::from("jeff-vader");
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2233,13 +2315,13 @@ XGo8Un24WP40IT78XjKO
 
         // CHECK jeff can update
         let new_meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader-2")),
+            twitter_id: Some(String::from("@jeff_vader_2")),
             parent_token_id: Some("this-token-id-should-be-ignored".to_string()),
             ..Metadata::default()
         };
 
         let expected_meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader-2")),
+            twitter_id: Some(String::from("@jeff_vader_2")),
             parent_token_id: None, // i.e. as it was
             ..Metadata::default()
         };
@@ -2271,7 +2353,7 @@ XGo8Un24WP40IT78XjKO
         let jeff_address = String::from("jeff-vader");
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2385,13 +2467,14 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
 
         let allowed = mock_info(&jeff_address, &[]);
         let _ = entry::instantiate(deps.as_mut(), mock_env(), allowed, init_msg);
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2637,13 +2720,14 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
 
         let allowed = mock_info(&jeff_address, &[]);
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -2845,6 +2929,7 @@ XGo8Un24WP40IT78XjKO
 
         // CHECK: this path info META is correct
         // i.e. it has been reset to the default
+        // but the path stays under its parent
         let info = contract
             .nft_info(deps.as_ref(), prepended_path_id_2)
             .unwrap();
@@ -2852,7 +2937,10 @@ XGo8Un24WP40IT78XjKO
             info,
             NftInfoResponse::<Extension> {
                 token_uri: Some(token_uri),
-                extension: default_meta,
+                extension: Metadata {
+                    parent_token_id: Some(prepended_path_id.clone()),
+                    ..default_meta
+                },
             }
         );
 
@@ -2926,13 +3014,14 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
 
         let allowed = mock_info(&jeff_address, &[]);
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -3357,13 +3446,14 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
 
         let allowed = mock_info(&jeff_address, &[]);
         entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -3786,6 +3876,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: String::from(MINTER),
             username_length_cap: None,
+            registration_model: None,
         };
         let info = mock_info("creator", &[]);
         let res = entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -3797,7 +3888,7 @@ XGo8Un24WP40IT78XjKO
         let jeff_address = String::from("jeff-vader");
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -3986,13 +4077,14 @@ XGo8Un24WP40IT78XjKO
             }),
             admin_address: jeff_address.clone(),
             username_length_cap: None,
+            registration_model: None,
         };
 
         let allowed = mock_info(&jeff_address, &[]);
         entry::instantiate(deps.as_mut(), mock_env(), allowed, init_msg).unwrap();
 
         let meta = Metadata {
-            twitter_id: Some(String::from("@jeff-vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
             ..Metadata::default()
         };
 
@@ -4268,6 +4360,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: "jeff-addr".to_string(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -4276,14 +4369,14 @@ XGo8Un24WP40IT78XjKO
         // that & MINTER do not need to be
         // as MINTER is the admin addr on the contract
         let token_id = "enterprise";
-        let contract_address = "contract-address".to_string();
-        let validator_address = "validator_address".to_string();
+        let contract_address = "juno1ejpjr43ht3y56pplm5pxpusmcrk9rkkvlnl5v7".to_string();
+        let validator_address = "junovaloper1lq40xgtqh3f3zt9prz4m74l6dlk506usrp7p6r".to_string();
         let mint_msg = MintMsg {
             token_id: token_id.to_string(),
             owner: CREATOR.to_string(),
             token_uri: Some("https://starships.example.com/Starship/Enterprise.json".into()),
             extension: Metadata {
-                twitter_id: Some(String::from("@jeff-vader")),
+                twitter_id: Some(String::from("@jeff_vader")),
                 contract_address: Some(contract_address.clone()),
                 validator_operator_address: Some(validator_address.clone()),
                 ..Metadata::default()
@@ -4350,6 +4443,7 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: "jeff-addr".to_string(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -4363,7 +4457,7 @@ XGo8Un24WP40IT78XjKO
             owner: CREATOR.to_string(),
             token_uri: Some("https://starships.example.com/Starship/Enterprise.json".into()),
             extension: Metadata {
-                twitter_id: Some(String::from("@jeff-vader")),
+                twitter_id: Some(String::from("@jeff_vader")),
                 ..Metadata::default()
             },
         };
@@ -4423,12 +4517,13 @@ XGo8Un24WP40IT78XjKO
             short_name_surcharge: None,
             admin_address: "jeff-addr".to_string(),
             username_length_cap: None,
+            registration_model: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
         // let's imagine this is a contract that does something
         // to do with the enterprise
-        let contract_address = "contract-address".to_string();
+        let contract_address = "juno1ejpjr43ht3y56pplm5pxpusmcrk9rkkvlnl5v7".to_string();
         let token_id = "enterprise-contract";
         let mint_msg = MintMsg {
             token_id: token_id.to_string(),
@@ -4481,4 +4576,5112 @@ XGo8Un24WP40IT78XjKO
             }
         );
     }
+
+    #[test]
+    fn children_and_paths_for_token_use_parent_index() {
+        let mut deps = mock_dependencies();
+        let jeff_address = String::from("jeff-vader");
+        let allowed = mock_info(&jeff_address, &[]);
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: Some(20),
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: jeff_address.clone(),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
+
+        // a couple of base tokens that sort before and after
+        // so they would crowd out a filtered page
+        for token_id in ["aaa", "jeffvader", "zzz"] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata::default(),
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+        }
+
+        // 12 paths under zzz, more than the default page size
+        for i in 0..12 {
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: format!("path-{:02}", i),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some("zzz".to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+        }
+
+        // then a path and a nested path under jeffvader
+        let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "vehicles".to_string(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("jeffvader".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+
+        let nested_path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "tie-fighter".to_string(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("jeffvader::vehicles".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            nested_path_mint_msg,
+        )
+        .unwrap();
+
+        // CHECK: children are only the direct descendants
+        let children_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "jeffvader".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(children_res.tokens, vec!["jeffvader::vehicles".to_string()]);
+
+        // CHECK: paths for token includes nested paths
+        // even though the owner has lots of other tokens
+        let paths_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathsForToken {
+                    owner: jeff_address.clone(),
+                    token_id: "jeffvader".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            paths_res.tokens,
+            vec![
+                "jeffvader::vehicles".to_string(),
+                "jeffvader::vehicles::tie-fighter".to_string()
+            ]
+        );
+
+        // CHECK: children paginate
+        let first_page: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "zzz".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(first_page.tokens.len(), 10);
+
+        let second_page: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "zzz".to_string(),
+                    start_after: first_page.tokens.last().cloned(),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            second_page.tokens,
+            vec!["zzz::path-10".to_string(), "zzz::path-11".to_string()]
+        );
+
+        // CHECK: paths for token paginates the same way
+        let paths_second_page: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathsForToken {
+                    owner: jeff_address.clone(),
+                    token_id: "zzz".to_string(),
+                    start_after: Some("zzz::path-09".to_string()),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(paths_second_page, second_page);
+
+        // CHECK: burning jeffvader removes the nested path too
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: "jeffvader".to_string(),
+        };
+        entry::execute(deps.as_mut(), mock_env(), allowed, burn_msg).unwrap();
+
+        let contract = Cw721MetadataContract::default();
+        let _ = contract
+            .nft_info(
+                deps.as_ref(),
+                "jeffvader::vehicles::tie-fighter".to_string(),
+            )
+            .unwrap_err();
+
+        let count = contract.num_tokens(deps.as_ref()).unwrap();
+        assert_eq!(14, count.count);
+
+        let children_after_burn: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "jeffvader::vehicles".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(children_after_burn.tokens, Vec::<String>::new());
+    }
+
+    #[test]
+    fn migrate_backfills_parent_index() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+
+        // tokens as they would have been stored
+        // before there was a parent index
+        let jeff = Addr::unchecked(MINTER);
+        for (token_id, parent_token_id) in [
+            ("jeffvader", None),
+            ("jeffvader::vehicles", Some("jeffvader")),
+            (
+                "jeffvader::vehicles::tie-fighter",
+                Some("jeffvader::vehicles"),
+            ),
+        ] {
+            let token = TokenInfo {
+                owner: jeff.clone(),
+                approvals: vec![],
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: parent_token_id.map(String::from),
+                    ..Metadata::default()
+                },
+            };
+            contract
+                .tokens
+                .save(&mut deps.storage, token_id, &token)
+                .unwrap();
+        }
+        // a path transferred before parents were kept on transfer
+        // was left with no parent
+        let legacy_path = TokenInfo {
+            owner: Addr::unchecked("john-vader"),
+            approvals: vec![],
+            token_uri: None,
+            extension: Metadata::default(),
+        };
+        contract
+            .tokens
+            .save(&mut deps.storage, "jeffvader::shop", &legacy_path)
+            .unwrap();
+        // enough base tokens that the backfill takes more than one batch
+        for i in 0..MIGRATION_BATCH_SIZE {
+            let token = TokenInfo {
+                owner: jeff.clone(),
+                approvals: vec![],
+                token_uri: None,
+                extension: Metadata::default(),
+            };
+            contract
+                .tokens
+                .save(&mut deps.storage, &format!("name{}", i), &token)
+                .unwrap();
+        }
+        contract
+            .token_count
+            .save(&mut deps.storage, &(MIGRATION_BATCH_SIZE as u64 + 4))
+            .unwrap();
+        PRIMARY_ALIASES
+            .save(&mut deps.storage, &jeff, &"jeffvader".to_string())
+            .unwrap();
+        LEGACY_CONTRACT_INFO
+            .save(
+                &mut deps.storage,
+                &ContractInfo {
+                    name: CONTRACT_NAME.to_string(),
+                    symbol: SYMBOL.to_string(),
+                },
+            )
+            .unwrap();
+
+        let migrate_fully = |deps: DepsMut| -> u32 {
+            let mut deps = deps;
+            entry::migrate(
+                deps.branch(),
+                mock_env(),
+                MigrateMsg {
+                    target_version: CONTRACT_VERSION.to_string(),
+                },
+            )
+            .unwrap();
+
+            let mut batches = 0;
+            loop {
+                batches += 1;
+                let res = entry::execute(
+                    deps.branch(),
+                    mock_env(),
+                    mock_info(MINTER, &[]),
+                    ExecuteMsg::MigrateBatch {},
+                )
+                .unwrap();
+                if res
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == "done" && attr.value == "true")
+                {
+                    return batches;
+                }
+            }
+        };
+
+        // CHECK: nothing else can run while the migration is in progress
+        entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                target_version: CONTRACT_VERSION.to_string(),
+            },
+        )
+        .unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "name0".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MigrationInProgress {});
+
+        // CHECK: only the admin can run a batch
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::MigrateBatch {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        assert!(migrate_fully(deps.as_mut()) > 1);
+
+        // CHECK: there is nothing left to do
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::MigrateBatch {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoMigrationInProgress {});
+
+        let paths_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathsForToken {
+                    owner: MINTER.to_string(),
+                    token_id: "jeffvader".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            paths_res.tokens,
+            vec![
+                "jeffvader::vehicles".to_string(),
+                "jeffvader::vehicles::tie-fighter".to_string()
+            ]
+        );
+
+        // only the base token is counted
+        let contract_info_res: ContractInfoResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            contract_info_res.base_token_count,
+            MIGRATION_BATCH_SIZE as u64 + 1
+        );
+        assert_eq!(PATH_COUNTS.load(&deps.storage, "jeffvader").unwrap(), 3);
+
+        // CHECK: migrating again doesn't count anything twice
+        migrate_fully(deps.as_mut());
+        let contract_info_res: ContractInfoResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            contract_info_res.base_token_count,
+            MIGRATION_BATCH_SIZE as u64 + 1
+        );
+        assert_eq!(PATH_COUNTS.load(&deps.storage, "jeffvader").unwrap(), 3);
+
+        // existing aliases are in the alias index
+        let addresses_res: AddressesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliasAddresses {
+                    token_id: "jeffvader".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(addresses_res.addresses, vec![MINTER.to_string()]);
+
+        // CHECK: the legacy path gets its parent back, and is indexed under it
+        let shop = contract
+            .tokens
+            .load(&deps.storage, "jeffvader::shop")
+            .unwrap();
+        assert_eq!(
+            shop.extension.parent_token_id,
+            Some("jeffvader".to_string())
+        );
+        assert!(PARENT_INDEX.has(&deps.storage, ("jeffvader", "jeffvader::shop")));
+
+        // CHECK: so it is burned along with its root
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Burn {
+                token_id: "jeffvader".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(contract
+            .tokens
+            .may_load(&deps.storage, "jeffvader::shop")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            PATH_COUNTS
+                .may_load(&deps.storage, "jeffvader")
+                .unwrap()
+                .unwrap_or_default(),
+            0
+        );
+    }
+
+    #[test]
+    fn large_subtree_burned_in_batches() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+        let jeff_address = String::from("jeff-vader");
+        let allowed = mock_info(&jeff_address, &[]);
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: jeff_address.clone(),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), allowed.clone(), init_msg).unwrap();
+
+        let token_id = "jeffvader".to_string();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg.clone()).unwrap();
+
+        // 30 paths, each with a nested path
+        // so 60 in total, more than fit in one batch
+        for i in 0..30 {
+            let path_id = format!("project-{:02}", i);
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.clone(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(token_id.clone()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+
+            let nested_path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: "plans".to_string(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(format!("{}::{}", token_id, path_id)),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                allowed.clone(),
+                nested_path_mint_msg,
+            )
+            .unwrap();
+        }
+
+        let count = contract.num_tokens(deps.as_ref()).unwrap();
+        assert_eq!(61, count.count);
+
+        // CHECK: burn only gets through the first batch
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        };
+        let res = entry::execute(deps.as_mut(), mock_env(), allowed.clone(), burn_msg).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "paths_burned" && attr.value == "false"));
+
+        let count = contract.num_tokens(deps.as_ref()).unwrap();
+        assert_eq!(10, count.count);
+
+        // CHECK: the name is locked until the rest are burned
+        let err = entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+
+        // CHECK: anyone can finish the job
+        let continue_msg = ExecuteMsg::ContinueBurnPaths {
+            token_id: token_id.clone(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            continue_msg.clone(),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "paths_burned" && attr.value == "true"));
+
+        // CHECK: nothing left behind
+        let count = contract.num_tokens(deps.as_ref()).unwrap();
+        assert_eq!(0, count.count);
+
+        let paths_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Paths {
+                    owner: jeff_address,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(paths_res.tokens, Vec::<String>::new());
+
+        // CHECK: nothing more to continue
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            continue_msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPathBurnPending {});
+
+        // CHECK: and the name can be claimed again
+        entry::execute(deps.as_mut(), mock_env(), allowed, mint_msg).unwrap();
+    }
+
+    #[test]
+    fn path_can_repeat_its_parent() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "dev".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "dev-tools".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("dev".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+
+        let nested_path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "dev".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("dev::dev-tools".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed, nested_path_mint_msg).unwrap();
+
+        // CHECK: the token ids are what we expect
+        let _ = contract
+            .nft_info(deps.as_ref(), "dev::dev-tools::dev".to_string())
+            .unwrap();
+
+        // CHECK: full paths resolve without mangling
+        let path_res: GetPathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFullPath {
+                    token_id: "dev::dev-tools".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(path_res.path, "dev::dev-tools");
+
+        let nested_path_res: GetPathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFullPath {
+                    token_id: "dev::dev-tools::dev".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(nested_path_res.path, "dev::dev-tools::dev");
+    }
+
+    #[test]
+    fn resolve_full_path() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let nested_mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "vehicles".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("jeffvader".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), nested_mint_msg).unwrap();
+
+        let path_meta = Metadata {
+            parent_token_id: Some("vehicles".to_string()),
+            contract_address: Some("juno1dav4aw3lgm82487skz5nevmrettvvmldhhqvrc".to_string()),
+            ..Metadata::default()
+        };
+        let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "tie-fighter".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: path_meta.clone(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed, path_mint_msg).unwrap();
+
+        // CHECK: GetFullPath and ResolvePath round trip
+        let path_res: GetPathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFullPath {
+                    token_id: "vehicles::tie-fighter".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(path_res.path, "jeffvader/vehicles::tie-fighter");
+
+        let resolve_res: ResolvePathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ResolvePath {
+                    path: "JeffVader/vehicles::tie-fighter".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            resolve_res,
+            ResolvePathResponse {
+                token_id: "vehicles::tie-fighter".to_string(),
+                owner: MINTER.to_string(),
+                contract_address: Some("juno1dav4aw3lgm82487skz5nevmrettvvmldhhqvrc".to_string()),
+                metadata: path_meta,
+            }
+        );
+
+        // CHECK: the segment that doesn't exist is reported
+        let err = entry::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolvePath {
+                path: "jeffvader/vehicles::x-wing".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Path segment vehicles::x-wing".to_string()
+            }
+        );
+
+        // CHECK: a partial path doesn't resolve
+        // as vehicles is under jeffvader
+        let err = entry::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolvePath {
+                path: "vehicles::tie-fighter".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Path segment vehicles is not a top level name")
+        );
+    }
+
+    #[test]
+    fn tree_of_paths() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let nested_mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "vehicles".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                parent_token_id: Some("jeffvader".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), nested_mint_msg).unwrap();
+
+        for (path_id, parent_token_id) in [
+            ("a", "jeffvader"),
+            ("b", "jeffvader"),
+            ("x", "jeffvader::a"),
+            ("y", "jeffvader::a"),
+            ("z", "jeffvader::b"),
+        ] {
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+        }
+
+        let node = |token_id: &str, children: Vec<TreeNode>| TreeNode {
+            token_id: token_id.to_string(),
+            owner: MINTER.to_string(),
+            metadata: None,
+            children,
+        };
+
+        // CHECK: the whole tree comes back nested
+        let tree_res: TreeResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tree {
+                    token_id: "jeffvader".to_string(),
+                    max_depth: None,
+                    limit: None,
+                    start_after: None,
+                    include_metadata: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            tree_res,
+            TreeResponse {
+                root: node(
+                    "jeffvader",
+                    vec![
+                        node(
+                            "jeffvader::a",
+                            vec![
+                                node("jeffvader::a::x", vec![]),
+                                node("jeffvader::a::y", vec![])
+                            ]
+                        ),
+                        node("jeffvader::b", vec![node("jeffvader::b::z", vec![])]),
+                        node("vehicles", vec![]),
+                    ]
+                ),
+                next_start_after: None,
+            }
+        );
+
+        // CHECK: depth is bounded
+        let shallow_tree_res: TreeResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tree {
+                    token_id: "jeffvader".to_string(),
+                    max_depth: Some(1),
+                    limit: None,
+                    start_after: None,
+                    include_metadata: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            shallow_tree_res.root,
+            node(
+                "jeffvader",
+                vec![
+                    node("jeffvader::a", vec![]),
+                    node("jeffvader::b", vec![]),
+                    node("vehicles", vec![]),
+                ]
+            )
+        );
+
+        // CHECK: node count is bounded, with a cursor to continue
+        let first_page: TreeResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tree {
+                    token_id: "jeffvader".to_string(),
+                    max_depth: None,
+                    limit: Some(3),
+                    start_after: None,
+                    include_metadata: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            first_page,
+            TreeResponse {
+                root: node(
+                    "jeffvader",
+                    vec![node(
+                        "jeffvader::a",
+                        vec![
+                            node("jeffvader::a::x", vec![]),
+                            node("jeffvader::a::y", vec![])
+                        ]
+                    )]
+                ),
+                next_start_after: Some("jeffvader::a::y".to_string()),
+            }
+        );
+
+        // CHECK: the next page repeats the ancestors of the cursor
+        let second_page: TreeResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tree {
+                    token_id: "jeffvader".to_string(),
+                    max_depth: None,
+                    limit: Some(3),
+                    start_after: first_page.next_start_after,
+                    include_metadata: Some(true),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(second_page.next_start_after, None);
+        let children: Vec<String> = second_page
+            .root
+            .children
+            .iter()
+            .map(|child| child.token_id.clone())
+            .collect();
+        assert_eq!(
+            children,
+            vec![
+                "jeffvader::a".to_string(),
+                "jeffvader::b".to_string(),
+                "vehicles".to_string()
+            ]
+        );
+        assert_eq!(
+            second_page.root.children[1].children[0].token_id,
+            "jeffvader::b::z"
+        );
+        assert_eq!(
+            second_page.root.children[1].metadata,
+            Some(Metadata {
+                parent_token_id: Some("jeffvader".to_string()),
+                ..Metadata::default()
+            })
+        );
+    }
+
+    #[test]
+    fn move_path() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        for (path_id, parent_token_id) in [
+            ("vehicles", "jeffvader"),
+            ("fleet", "jeffvader"),
+            ("tie-fighter", "jeffvader::vehicles"),
+            ("wings", "jeffvader::vehicles::tie-fighter"),
+        ] {
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    public_name: Some(path_id.to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+        }
+
+        // set up an alias and an approval to carry over
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "jeffvader::vehicles::tie-fighter".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::Approve {
+                spender: "random-guy".to_string(),
+                token_id: "jeffvader::vehicles::tie-fighter".to_string(),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+        let move_msg = ExecuteMsg::MovePath {
+            token_id: "jeffvader::vehicles::tie-fighter".to_string(),
+            new_parent: Some("jeffvader::fleet".to_string()),
+            new_name: Some("tie-interceptor".to_string()),
+        };
+
+        // CHECK: random cannot move it
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            move_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: can't move a path under itself
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::MovePath {
+                token_id: "jeffvader::vehicles".to_string(),
+                new_parent: Some("jeffvader::vehicles::tie-fighter".to_string()),
+                new_name: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CycleDetected {});
+
+        // CHECK: jeff can
+        entry::execute(deps.as_mut(), mock_env(), allowed, move_msg).unwrap();
+
+        let count = contract.num_tokens(deps.as_ref()).unwrap();
+        assert_eq!(5, count.count);
+
+        let _ = contract
+            .nft_info(
+                deps.as_ref(),
+                "jeffvader::vehicles::tie-fighter".to_string(),
+            )
+            .unwrap_err();
+        let _ = contract
+            .nft_info(
+                deps.as_ref(),
+                "jeffvader::vehicles::tie-fighter::wings".to_string(),
+            )
+            .unwrap_err();
+
+        // CHECK: metadata comes along, with the new parent
+        let info = contract
+            .nft_info(
+                deps.as_ref(),
+                "jeffvader::fleet::tie-interceptor::wings".to_string(),
+            )
+            .unwrap();
+        assert_eq!(
+            info.extension,
+            Metadata {
+                parent_token_id: Some("jeffvader::fleet::tie-interceptor".to_string()),
+                public_name: Some("wings".to_string()),
+                ..Metadata::default()
+            }
+        );
+
+        // CHECK: approvals come along
+        let owner = contract
+            .owner_of(
+                deps.as_ref(),
+                mock_env(),
+                "jeffvader::fleet::tie-interceptor".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(owner.approvals[0].spender, "random-guy");
+
+        // CHECK: alias follows the path
+        let alias_query_res: PrimaryAliasResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAlias {
+                    address: MINTER.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            alias_query_res.username,
+            "jeffvader::fleet::tie-interceptor"
+        );
+
+        // CHECK: the tree has been rearranged
+        let vehicles_paths: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathsForToken {
+                    owner: MINTER.to_string(),
+                    token_id: "jeffvader::vehicles".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(vehicles_paths.tokens, Vec::<String>::new());
+
+        let fleet_paths: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathsForToken {
+                    owner: MINTER.to_string(),
+                    token_id: "jeffvader::fleet".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            fleet_paths.tokens,
+            vec![
+                "jeffvader::fleet::tie-interceptor".to_string(),
+                "jeffvader::fleet::tie-interceptor::wings".to_string()
+            ]
+        );
+
+        let path_res: GetPathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFullPath {
+                    token_id: "jeffvader::fleet::tie-interceptor::wings".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(path_res.path, "jeffvader::fleet::tie-interceptor::wings");
+
+        // jeff sells wings to random-guy
+        // who has a name of his own
+        let random = mock_info("random-guy", &[]);
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::TransferNft {
+                recipient: "random-guy".to_string(),
+                token_id: "jeffvader::fleet::tie-interceptor::wings".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            random.clone(),
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "randomguy".to_string(),
+                owner: "random-guy".to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            }),
+        )
+        .unwrap();
+
+        // CHECK: he can't take it out of jeff's namespace, or rename it there
+        // as he doesn't own the parent it is under
+        for (new_parent, new_name) in [
+            (Some("randomguy".to_string()), None),
+            (None, Some("x-wing".to_string())),
+        ] {
+            let err = entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                random.clone(),
+                ExecuteMsg::MovePath {
+                    token_id: "jeffvader::fleet::tie-interceptor::wings".to_string(),
+                    new_parent,
+                    new_name,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+    }
+
+    #[test]
+    fn path_limits() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let path_mint_msg = |path_id: &str, parent_token_id: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+
+        // CHECK: no limits by default
+        let limits_res: PathLimits =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), QueryMsg::PathLimits {}).unwrap())
+                .unwrap();
+        assert_eq!(limits_res, PathLimits::default());
+
+        let new_limits = PathLimits {
+            max_depth: Some(2),
+            max_paths_per_namespace: Some(3),
+            max_segment_length: Some(8),
+        };
+
+        // CHECK: random cannot set limits
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::UpdatePathLimits(new_limits.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: admin can
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdatePathLimits(new_limits.clone()),
+        )
+        .unwrap();
+
+        let limits_res: PathLimits =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), QueryMsg::PathLimits {}).unwrap())
+                .unwrap();
+        assert_eq!(limits_res, new_limits);
+
+        // CHECK: segment length is capped
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("construction", "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathSegmentTooLong {});
+
+        // CHECK: depth is capped
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("vehicles", "jeffvader"),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("tie", "jeffvader::vehicles"),
+        )
+        .unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("wings", "jeffvader::vehicles::tie"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathTooDeep {});
+
+        // CHECK: paths per namespace are capped
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("plans", "jeffvader"),
+        )
+        .unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("secrets", "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NamespacePathLimitReached {});
+
+        // CHECK: burning a path frees up room
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::Burn {
+                token_id: "jeffvader::plans".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            path_mint_msg("secrets", "jeffvader"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn path_minting_fees() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+        let bob = "random-guy";
+
+        // admin sets a protocol fee for paths
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdateMintingFees(UpdateMintingFeesMsg {
+                token_cap: Some(2),
+                base_mint_fee: None,
+                burn_percentage: Some(50),
+                short_name_surcharge: None,
+                path_mint_fee: Some(Uint128::new(100)),
+                bonding_curve: None,
+            }),
+        )
+        .unwrap();
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let namespace_fee_msg = ExecuteMsg::UpdateNamespaceFee {
+            token_id: "jeffvader".to_string(),
+            fee: Some(Uint128::new(1000)),
+        };
+
+        // CHECK: only the owner can set a namespace fee
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(bob, &[]),
+            namespace_fee_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            namespace_fee_msg,
+        )
+        .unwrap();
+
+        // CHECK: price is quoted
+        let price_res: PathMintPriceResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PathMintPrice {
+                    parent_token_id: "jeffvader".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            price_res,
+            PathMintPriceResponse {
+                native_denom: "uatom".to_string(),
+                protocol_fee: Some(Uint128::new(100)),
+                namespace_fee: Some(Uint128::new(1000)),
+                total: Uint128::new(1100),
+            }
+        );
+
+        let path_mint_msg = |path_id: &str, owner: &str, parent_token_id: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+
+        // CHECK: the protocol fee must be paid
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("vehicles", MINTER, "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(cw_utils::PaymentError::NoFunds {})
+        );
+
+        // CHECK: the owner doesn't pay themselves
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &coins(100, "uatom")),
+            path_mint_msg("vehicles", MINTER, "jeffvader"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|sub_msg| sub_msg.msg)
+                .collect::<Vec<CosmosMsg>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: MINTER.to_string(),
+                    amount: coins(50, "uatom"),
+                }),
+                CosmosMsg::Bank(BankMsg::Burn {
+                    amount: coins(50, "uatom"),
+                }),
+            ]
+        );
+
+        // bob buys the path, and then wants to add to it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            ExecuteMsg::TransferNft {
+                recipient: bob.to_string(),
+                token_id: "jeffvader::vehicles".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: bob has to pay both
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(bob, &coins(100, "uatom")),
+            path_mint_msg("tie-fighter", bob, "jeffvader::vehicles"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(bob, &coins(1100, "uatom")),
+            path_mint_msg("tie-fighter", bob, "jeffvader::vehicles"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|sub_msg| sub_msg.msg)
+                .collect::<Vec<CosmosMsg>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: MINTER.to_string(),
+                    amount: coins(50, "uatom"),
+                }),
+                CosmosMsg::Bank(BankMsg::Burn {
+                    amount: coins(50, "uatom"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: MINTER.to_string(),
+                    amount: coins(1000, "uatom"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn path_metadata_inheritance() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "acme".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                public_name: Some("Acme".to_string()),
+                contract_address: Some("juno1sg4n8tv8c9y2pgs2twnu6h4u4f5dx6sc7essem".to_string()),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        for (path_id, parent_token_id, public_name) in [
+            ("payments", "acme", Some("Acme Payments")),
+            ("eu", "acme::payments", None),
+        ] {
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    public_name: public_name.map(|n| n.to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+        }
+
+        let address_of = |deps: Deps, token_id: &str| -> AddressOfResponse {
+            from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::AddressOf {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // CHECK: nothing is inherited by default
+        assert_eq!(
+            address_of(deps.as_ref(), "acme::payments::eu").contract_address,
+            None
+        );
+
+        let inherit_msg = |token_id: &str| ExecuteMsg::UpdateMetadataInheritance {
+            token_id: token_id.to_string(),
+            inherit: true,
+        };
+
+        // CHECK: only the owner can opt in
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            inherit_msg("acme::payments::eu"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            inherit_msg("acme::payments::eu"),
+        )
+        .unwrap();
+
+        // CHECK: unset fields fall back to the nearest ancestor
+        assert_eq!(
+            address_of(deps.as_ref(), "acme::payments::eu").contract_address,
+            Some("juno1sg4n8tv8c9y2pgs2twnu6h4u4f5dx6sc7essem".to_string())
+        );
+        let resolved_res: ResolvedMetadataResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ResolvedMetadata {
+                    token_id: "acme::payments::eu".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            resolved_res,
+            ResolvedMetadataResponse {
+                token_id: "acme::payments::eu".to_string(),
+                inherit: true,
+                metadata: Metadata {
+                    parent_token_id: Some("acme::payments".to_string()),
+                    public_name: Some("Acme Payments".to_string()),
+                    contract_address: Some(
+                        "juno1sg4n8tv8c9y2pgs2twnu6h4u4f5dx6sc7essem".to_string()
+                    ),
+                    ..Metadata::default()
+                },
+                sources: vec![
+                    MetadataFieldSource {
+                        field: "public_name".to_string(),
+                        token_id: "acme::payments".to_string(),
+                    },
+                    MetadataFieldSource {
+                        field: "contract_address".to_string(),
+                        token_id: "acme".to_string(),
+                    },
+                ],
+            }
+        );
+
+        // CHECK: a moved path keeps inheriting, from its new ancestors
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::MovePath {
+                token_id: "acme::payments::eu".to_string(),
+                new_parent: Some("acme".to_string()),
+                new_name: None,
+            },
+        )
+        .unwrap();
+        let resolved_res: ResolvedMetadataResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ResolvedMetadata {
+                    token_id: "acme::eu".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(resolved_res.inherit);
+        assert_eq!(resolved_res.metadata.public_name, Some("Acme".to_string()));
+
+        // CHECK: opting out leaves only its own metadata
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            ExecuteMsg::UpdateMetadataInheritance {
+                token_id: "acme::eu".to_string(),
+                inherit: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(address_of(deps.as_ref(), "acme::eu").contract_address, None);
+    }
+
+    #[test]
+    fn fuses() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: MINTER.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        let path_mint_msg = |path_id: &str, parent_token_id: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("vehicles", "jeffvader"),
+        )
+        .unwrap();
+
+        let query_fuses = |deps: Deps, token_id: &str| -> Fuses {
+            from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::Fuses {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(query_fuses(deps.as_ref(), "jeffvader"), Fuses::default());
+
+        let path_fuses_msg = ExecuteMsg::BurnFuses {
+            token_id: "jeffvader::vehicles".to_string(),
+            fuses: Fuses {
+                cannot_mint_paths: true,
+                cannot_update_metadata: true,
+                ..Fuses::default()
+            },
+        };
+
+        // CHECK: a path can't be fused while its parent can still be burned
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_fuses_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ParentNotFused {});
+
+        let base_fuses_msg = ExecuteMsg::BurnFuses {
+            token_id: "jeffvader".to_string(),
+            fuses: Fuses {
+                cannot_transfer: true,
+                cannot_burn: true,
+                ..Fuses::default()
+            },
+        };
+
+        // CHECK: only the owner can burn fuses
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            base_fuses_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), base_fuses_msg).unwrap();
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_fuses_msg).unwrap();
+
+        // CHECK: fuses can't be unburned
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::BurnFuses {
+                token_id: "jeffvader".to_string(),
+                fuses: Fuses::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            query_fuses(deps.as_ref(), "jeffvader"),
+            Fuses {
+                cannot_transfer: true,
+                cannot_burn: true,
+                ..Fuses::default()
+            }
+        );
+
+        // CHECK: fused actions are prohibited
+        let prohibited = [
+            ExecuteMsg::TransferNft {
+                recipient: "random-guy".to_string(),
+                token_id: "jeffvader".to_string(),
+            },
+            ExecuteMsg::Burn {
+                token_id: "jeffvader".to_string(),
+            },
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: "jeffvader::vehicles".to_string(),
+                metadata: Metadata::default(),
+            }),
+            path_mint_msg("tie-fighter", "jeffvader::vehicles"),
+            ExecuteMsg::MovePath {
+                token_id: "jeffvader::vehicles".to_string(),
+                new_parent: None,
+                new_name: Some("fleet".to_string()),
+            },
+        ];
+        for msg in prohibited {
+            let err = entry::execute(deps.as_mut(), mock_env(), allowed.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::FuseBurned {});
+        }
+
+        // CHECK: anything not fused still works
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: "jeffvader".to_string(),
+                metadata: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            path_mint_msg("fleet", "jeffvader"),
+        )
+        .unwrap();
+
+        // CHECK: a path can't be moved under a parent
+        // that can't have paths minted under it either
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            ExecuteMsg::MovePath {
+                token_id: "jeffvader::fleet".to_string(),
+                new_parent: Some("jeffvader::vehicles".to_string()),
+                new_name: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::FuseBurned {});
+        assert!(!PATHS.has(&deps.storage, "jeffvader::vehicles::fleet"));
+    }
+
+    #[test]
+    fn rename() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        // short names cost extra
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdateMintingFees(UpdateMintingFeesMsg {
+                token_cap: Some(2),
+                base_mint_fee: None,
+                burn_percentage: Some(50),
+                short_name_surcharge: Some(SurchargeInfo {
+                    surcharge_max_characters: 6,
+                    surcharge_fee: Uint128::new(1000),
+                }),
+                path_mint_fee: None,
+                bonding_curve: None,
+            }),
+        )
+        .unwrap();
+
+        for (token_id, parent_token_id) in [("jeffvader", None), ("deathstar", Some("jeffvader"))] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: parent_token_id.map(|p| p.to_string()),
+                    public_name: Some(token_id.to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+        }
+
+        for (path_id, parent_token_id) in [
+            ("vehicles", "jeffvader"),
+            ("tie-fighter", "jeffvader::vehicles"),
+        ] {
+            let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent_token_id.to_string()),
+                    ..Metadata::default()
+                },
+            });
+            entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+        }
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "jeffvader".to_string(),
+            },
+        )
+        .unwrap();
+
+        let rename_msg = ExecuteMsg::Rename {
+            token_id: "jeffvader".to_string(),
+            new_token_id: "Vader".to_string(),
+        };
+
+        // CHECK: random cannot rename it
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            rename_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: paths are renamed with MovePath
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::Rename {
+                token_id: "jeffvader::vehicles".to_string(),
+                new_token_id: "fleet".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TokenNameInvalid {});
+
+        // CHECK: the surcharge for the shorter name must be paid
+        let err =
+            entry::execute(deps.as_mut(), mock_env(), allowed, rename_msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(cw_utils::PaymentError::NoFunds {})
+        );
+
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &coins(1000, "uatom")),
+            rename_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|sub_msg| sub_msg.msg)
+                .collect::<Vec<CosmosMsg>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: MINTER.to_string(),
+                    amount: coins(500, "uatom"),
+                }),
+                CosmosMsg::Bank(BankMsg::Burn {
+                    amount: coins(500, "uatom"),
+                }),
+            ]
+        );
+
+        // CHECK: the old name is gone
+        assert!(contract.tokens.load(&deps.storage, "jeffvader").is_err());
+        assert!(contract
+            .tokens
+            .load(&deps.storage, "jeffvader::vehicles")
+            .is_err());
+
+        // CHECK: metadata, paths and nested names come along
+        let renamed = contract.tokens.load(&deps.storage, "vader").unwrap();
+        assert_eq!(renamed.extension.public_name, Some("jeffvader".to_string()));
+        let path = contract
+            .tokens
+            .load(&deps.storage, "vader::vehicles::tie-fighter")
+            .unwrap();
+        assert_eq!(
+            path.extension.parent_token_id,
+            Some("vader::vehicles".to_string())
+        );
+        let nested = contract.tokens.load(&deps.storage, "deathstar").unwrap();
+        assert_eq!(nested.extension.parent_token_id, Some("vader".to_string()));
+
+        let children_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "vader".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            children_res.tokens,
+            vec!["deathstar".to_string(), "vader::vehicles".to_string()]
+        );
+
+        let full_path_res: GetPathResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetFullPath {
+                    token_id: "vader::vehicles::tie-fighter".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(full_path_res.path, "vader::vehicles::tie-fighter");
+
+        // CHECK: the primary alias follows the rename
+        let alias_res: PrimaryAliasResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAlias {
+                    address: MINTER.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(alias_res.username, "vader");
+
+        // CHECK: nothing is refunded for a longer name
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Rename {
+                token_id: "vader".to_string(),
+                new_token_id: "darthvader".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 0);
+    }
+
+    #[test]
+    fn rate_limits() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        let env_at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let mint_msg = |token_id: &str, owner: &str| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            })
+        };
+        let path_mint_msg = |path_id: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some("jeffvader".to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+        let allowance = |deps: Deps, height: u64, address: &str| -> MintAllowanceResponse {
+            from_binary(
+                &entry::query(
+                    deps,
+                    env_at(height),
+                    QueryMsg::MintAllowance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // CHECK: no limits by default
+        assert_eq!(
+            allowance(deps.as_ref(), 100, MINTER),
+            MintAllowanceResponse {
+                address_remaining: None,
+                block_remaining: None,
+            }
+        );
+
+        let new_limits = RateLimits {
+            max_mints_per_address: Some(2),
+            window_blocks: Some(3),
+            max_mints_per_block: Some(3),
+        };
+
+        // CHECK: random cannot set limits
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::UpdateRateLimits(new_limits.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdateRateLimits(new_limits.clone()),
+        )
+        .unwrap();
+        let limits_res: RateLimits =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), QueryMsg::RateLimits {}).unwrap())
+                .unwrap();
+        assert_eq!(limits_res, new_limits);
+
+        // CHECK: names and paths both count against the address
+        entry::execute(
+            deps.as_mut(),
+            env_at(100),
+            allowed.clone(),
+            mint_msg("jeffvader", MINTER),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            env_at(100),
+            allowed.clone(),
+            path_mint_msg("a"),
+        )
+        .unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            env_at(100),
+            allowed.clone(),
+            path_mint_msg("b"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AddressMintLimitReached {});
+        assert_eq!(
+            allowance(deps.as_ref(), 100, MINTER),
+            MintAllowanceResponse {
+                address_remaining: Some(0),
+                block_remaining: Some(1),
+            }
+        );
+
+        // CHECK: everyone counts against the block
+        entry::execute(
+            deps.as_mut(),
+            env_at(100),
+            mock_info("bob", &[]),
+            mint_msg("bobby", "bob"),
+        )
+        .unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            env_at(100),
+            mock_info("carl", &[]),
+            mint_msg("carl", "carl"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BlockMintLimitReached {});
+        entry::execute(
+            deps.as_mut(),
+            env_at(101),
+            mock_info("carl", &[]),
+            mint_msg("carl", "carl"),
+        )
+        .unwrap();
+
+        // CHECK: the window slides
+        let err = entry::execute(
+            deps.as_mut(),
+            env_at(102),
+            allowed.clone(),
+            path_mint_msg("b"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AddressMintLimitReached {});
+        assert_eq!(
+            allowance(deps.as_ref(), 103, MINTER),
+            MintAllowanceResponse {
+                address_remaining: Some(2),
+                block_remaining: Some(3),
+            }
+        );
+        entry::execute(deps.as_mut(), env_at(103), allowed, path_mint_msg("b")).unwrap();
+    }
+
+    #[test]
+    fn name_categories_test() {
+        assert!(is_numeric_name("007"));
+        assert!(!is_numeric_name("007-bond"));
+        assert!(is_palindrome_name("abba"));
+        assert!(is_palindrome_name("a-b-a"));
+        assert!(!is_palindrome_name("a"));
+        assert!(!is_palindrome_name("abc"));
+        assert!(is_repeated_character_name("zzz"));
+        assert!(!is_repeated_character_name("z"));
+        assert!(!is_repeated_character_name("zzy"));
+    }
+
+    #[test]
+    fn name_category_pricing() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::UpdateMintingFees(UpdateMintingFeesMsg {
+                token_cap: Some(2),
+                base_mint_fee: Some(Uint128::new(100)),
+                burn_percentage: Some(50),
+                short_name_surcharge: None,
+                path_mint_fee: None,
+                bonding_curve: None,
+            }),
+        )
+        .unwrap();
+
+        // repeated characters come first, so 777 is not priced as numeric
+        let categories = vec![
+            NameCategoryPricing {
+                category: NameCategory::RepeatedCharacter,
+                enabled: false,
+                fee_multiplier: Decimal::one(),
+            },
+            NameCategoryPricing {
+                category: NameCategory::Numeric,
+                enabled: true,
+                fee_multiplier: Decimal::percent(500),
+            },
+            NameCategoryPricing {
+                category: NameCategory::Palindrome,
+                enabled: true,
+                fee_multiplier: Decimal::percent(50),
+            },
+        ];
+        let update_msg = ExecuteMsg::UpdateNameCategories {
+            categories: categories.clone(),
+        };
+
+        // CHECK: random cannot set categories
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), update_msg).unwrap();
+
+        // CHECK: the quote reports the category
+        let mint_price = |deps: Deps, token_id: &str| -> MintPriceResponse {
+            from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::MintPrice {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        for (token_id, category, enabled, fee) in [
+            ("jeffvader", None, true, 100),
+            ("007", Some(NameCategory::Numeric), true, 500),
+            ("abba", Some(NameCategory::Palindrome), true, 50),
+            ("777", Some(NameCategory::RepeatedCharacter), false, 100),
+        ] {
+            assert_eq!(
+                mint_price(deps.as_ref(), token_id),
+                MintPriceResponse {
+                    native_denom: "uatom".to_string(),
+                    category,
+                    enabled,
+                    fee: Some(Uint128::new(fee)),
+                }
+            );
+        }
+
+        let mint_msg = |token_id: &str| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            })
+        };
+
+        // CHECK: disabled categories can't be minted
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &coins(100, "uatom")),
+            mint_msg("777"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NameCategoryDisabled {});
+
+        // CHECK: the category's price is charged
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &coins(100, "uatom")),
+            mint_msg("007"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &coins(500, "uatom")),
+            mint_msg("007"),
+        )
+        .unwrap();
+
+        let categories_res: NameCategoriesResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::NameCategories {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(categories_res.categories, categories);
+    }
+
+    #[test]
+    fn bonding_curve() {
+        let mut deps = mock_dependencies();
+        let _ = setup_contract(deps.as_mut());
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateMintingFees(UpdateMintingFeesMsg {
+                token_cap: Some(2),
+                base_mint_fee: Some(Uint128::new(100)),
+                burn_percentage: Some(50),
+                short_name_surcharge: Some(SurchargeInfo {
+                    surcharge_max_characters: 5,
+                    surcharge_fee: Uint128::new(1000),
+                }),
+                path_mint_fee: None,
+                bonding_curve: Some(BondingCurve {
+                    base_names_per_step: 2,
+                    fee_increase_per_step: Uint128::new(50),
+                    max_base_mint_fee: Some(Uint128::new(180)),
+                }),
+            }),
+        )
+        .unwrap();
+
+        let mint_price = |deps: Deps, token_id: &str| -> Option<Uint128> {
+            let res: MintPriceResponse = from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::MintPrice {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.fee
+        };
+        let mint = |deps: DepsMut, owner: &str, token_id: &str, fee: u128| {
+            entry::execute(
+                deps,
+                mock_env(),
+                mock_info(owner, &coins(fee, "uatom")),
+                ExecuteMsg::Mint(MintMsg {
+                    token_id: token_id.to_string(),
+                    owner: owner.to_string(),
+                    token_uri: None,
+                    extension: Metadata::default(),
+                }),
+            )
+        };
+
+        // CHECK: early adopters pay the base fee
+        assert_eq!(
+            mint_price(deps.as_ref(), "jeffvader"),
+            Some(Uint128::new(100))
+        );
+        mint(deps.as_mut(), "jeff", "jeffvader", 100).unwrap();
+        mint(deps.as_mut(), "luke", "lukeskywalker", 100).unwrap();
+
+        // CHECK: the fee goes up a step
+        let err = mint(deps.as_mut(), "leia", "leiaorgana", 100).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+        assert_eq!(
+            mint_price(deps.as_ref(), "leiaorgana"),
+            Some(Uint128::new(150))
+        );
+
+        let contract_info_res: ContractInfoResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(contract_info_res.base_token_count, 2);
+        assert_eq!(
+            contract_info_res.current_base_mint_fee,
+            Some(Uint128::new(150))
+        );
+
+        // CHECK: paths don't count
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jeff", &[]),
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: "vehicles".to_string(),
+                owner: "jeff".to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some("jeffvader".to_string()),
+                    ..Metadata::default()
+                },
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            mint_price(deps.as_ref(), "leiaorgana"),
+            Some(Uint128::new(150))
+        );
+
+        // CHECK: the fee is capped, and combines with the surcharge
+        mint(deps.as_mut(), "leia", "leiaorgana", 150).unwrap();
+        mint(deps.as_mut(), "han", "hansolo", 150).unwrap();
+        assert_eq!(
+            mint_price(deps.as_ref(), "chewbacca"),
+            Some(Uint128::new(180))
+        );
+        assert_eq!(mint_price(deps.as_ref(), "r2d2"), Some(Uint128::new(1180)));
+
+        // CHECK: burning a base name brings the fee back down
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("han", &[]),
+            ExecuteMsg::Burn {
+                token_id: "hansolo".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            mint_price(deps.as_ref(), "chewbacca"),
+            Some(Uint128::new(150))
+        );
+    }
+
+    #[test]
+    fn deposit_registration() {
+        let mut deps = mock_dependencies();
+        let jeff = "jeff-vader";
+        let bob = "random-guy";
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: Some(Uint128::new(1000)),
+            burn_percentage: Some(50),
+            short_name_surcharge: None,
+            admin_address: MINTER.to_string(),
+            username_length_cap: None,
+            registration_model: Some(RegistrationModel::Deposit),
+        };
+        entry::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            init_msg,
+        )
+        .unwrap();
+
+        let contract_info_res: ContractInfoResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            contract_info_res.registration_model,
+            RegistrationModel::Deposit
+        );
+
+        // CHECK: the fee is held, not paid out
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(jeff, &coins(1000, "uatom")),
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "jeffvader".to_string(),
+                owner: jeff.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 0);
+
+        let deposit_query = QueryMsg::Deposit {
+            token_id: "jeffvader".to_string(),
+        };
+        let deposit_res: DepositResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), deposit_query.clone()).unwrap())
+                .unwrap();
+        assert_eq!(
+            deposit_res,
+            DepositResponse {
+                native_denom: "uatom".to_string(),
+                amount: Uint128::new(1000),
+            }
+        );
+
+        // CHECK: the deposit goes with the name
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(jeff, &[]),
+            ExecuteMsg::TransferNft {
+                recipient: bob.to_string(),
+                token_id: "jeffvader".to_string(),
+            },
+        )
+        .unwrap();
+        let deposit_res: DepositResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), deposit_query.clone()).unwrap())
+                .unwrap();
+        assert_eq!(deposit_res.amount, Uint128::new(1000));
+
+        // CHECK: and is returned to the owner on burn
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(bob, &[]),
+            ExecuteMsg::Burn {
+                token_id: "jeffvader".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|sub_msg| sub_msg.msg)
+                .collect::<Vec<CosmosMsg>>(),
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: bob.to_string(),
+                amount: coins(1000, "uatom"),
+            })]
+        );
+        assert!(entry::query(deps.as_ref(), mock_env(), deposit_query).is_err());
+    }
+
+    #[test]
+    fn primary_aliases_batch() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let john_address = String::from("john-vader");
+
+        for (token_id, owner) in [
+            ("jeffisthebest", &jeff_address),
+            ("jeffisbetterthanjohn", &jeff_address),
+            ("johnisthebest", &john_address),
+        ] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: owner.clone(),
+                token_uri: None,
+                extension: Metadata::default(),
+            });
+            entry::execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), mint_msg).unwrap();
+        }
+
+        // jeff explicitly sets an alias, john relies on the fallback
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&jeff_address, &[]),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "jeffisthebest".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: one entry per address, in order,
+        // and neither an unknown nor an invalid address fails the batch
+        let res: PrimaryAliasesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliases {
+                    addresses: vec![
+                        john_address.clone(),
+                        "nobody".to_string(),
+                        jeff_address.clone(),
+                        "".to_string(),
+                    ],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            res.aliases,
+            vec![
+                PrimaryAliasEntry {
+                    address: john_address,
+                    username: Some("johnisthebest".to_string()),
+                    source: PrimaryAliasSource::FirstToken,
+                },
+                PrimaryAliasEntry {
+                    address: "nobody".to_string(),
+                    username: None,
+                    source: PrimaryAliasSource::None,
+                },
+                PrimaryAliasEntry {
+                    address: jeff_address,
+                    username: Some("jeffisthebest".to_string()),
+                    source: PrimaryAliasSource::Explicit,
+                },
+                PrimaryAliasEntry {
+                    address: "".to_string(),
+                    username: None,
+                    source: PrimaryAliasSource::None,
+                },
+            ]
+        );
+
+        // CHECK: at most 100 addresses can be looked up at once
+        let addresses: Vec<String> = (0..MAX_ALIAS_BATCH)
+            .map(|i| format!("address-{}", i))
+            .collect();
+        let res: PrimaryAliasesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliases {
+                    addresses: addresses.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.aliases.len(), MAX_ALIAS_BATCH);
+
+        let err = entry::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PrimaryAliases {
+                addresses: [addresses, vec!["address-100".to_string()]].concat(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Too many addresses, at most 100 can be looked up at once")
+        );
+    }
+
+    #[test]
+    fn profile() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let allowed = mock_info(&jeff_address, &[]);
+
+        let profile_query = QueryMsg::Profile {
+            address: jeff_address.clone(),
+        };
+
+        // CHECK: no tokens, no profile
+        assert!(entry::query(deps.as_ref(), mock_env(), profile_query.clone()).is_err());
+
+        let meta = Metadata {
+            twitter_id: Some(String::from("@jeff_vader")),
+            ..Metadata::default()
+        };
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffvader".to_string(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: meta.clone(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), mint_msg).unwrap();
+
+        // CHECK: falls back to the first token
+        let res: ProfileResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), profile_query.clone()).unwrap())
+                .unwrap();
+        assert_eq!(
+            res,
+            ProfileResponse {
+                username: "jeffvader".to_string(),
+                owner: jeff_address.clone(),
+                metadata: meta,
+                path: "jeffvader".to_string(),
+                source: PrimaryAliasSource::FirstToken,
+            }
+        );
+
+        let path_meta = Metadata {
+            parent_token_id: Some("jeffvader".to_string()),
+            discord_id: Some(String::from("jeffvader#0001")),
+            ..Metadata::default()
+        };
+        let path_mint_msg = ExecuteMsg::MintPath(MintMsg {
+            token_id: "vehicles".to_string(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: path_meta.clone(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), allowed.clone(), path_mint_msg).unwrap();
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "jeffvader::vehicles".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: explicit alias comes back with its metadata and full path
+        let res: ProfileResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), profile_query).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            ProfileResponse {
+                username: "jeffvader::vehicles".to_string(),
+                owner: jeff_address,
+                metadata: path_meta,
+                path: "jeffvader::vehicles".to_string(),
+                source: PrimaryAliasSource::Explicit,
+            }
+        );
+    }
+
+    #[test]
+    fn primary_alias_policy() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let john_address = String::from("john-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+
+        for token_id in ["jeffone", "jefftwo", "jeffthree", "jefffour"] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata::default(),
+            });
+            entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+        }
+        let john_mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "johnone".to_string(),
+            owner: john_address.clone(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&john_address, &[]),
+            john_mint_msg,
+        )
+        .unwrap();
+
+        let primary_alias_of = |deps: Deps, address: &str| -> StdResult<String> {
+            let res: PrimaryAliasResponse = from_binary(&entry::query(
+                deps,
+                mock_env(),
+                QueryMsg::PrimaryAlias {
+                    address: address.to_string(),
+                },
+            )?)?;
+            Ok(res.username)
+        };
+
+        // CHECK: defaults to falling back to the first token
+        let policy: PrimaryAliasPolicy = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliasPolicy {
+                    address: jeff_address.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(policy, PrimaryAliasPolicy::FirstToken);
+
+        // CHECK: backups must be owned by the sender
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdatePrimaryAliasPolicy {
+                policy: PrimaryAliasPolicy::Backups {
+                    aliases: vec!["jefftwo".to_string(), "johnone".to_string()],
+                },
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: and there is a cap on how many
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdatePrimaryAliasPolicy {
+                policy: PrimaryAliasPolicy::Backups {
+                    aliases: vec!["jefftwo".to_string(); 11],
+                },
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyBackupAliases {});
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdatePrimaryAliasPolicy {
+                policy: PrimaryAliasPolicy::Backups {
+                    aliases: vec!["jefftwo".to_string(), "jeffthree".to_string()],
+                },
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "jeffone".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: a receiver with no primary alias gets the name as primary
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: john_address.clone(),
+                token_id: "jeffone".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &john_address).unwrap(),
+            "jeffone"
+        );
+
+        // CHECK: and the sender moves on to their first backup
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &jeff_address).unwrap(),
+            "jefftwo"
+        );
+
+        // CHECK: then the next one when that is burned
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::Burn {
+                token_id: "jefftwo".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &jeff_address).unwrap(),
+            "jeffthree"
+        );
+
+        // CHECK: a receiver that already has a primary alias keeps it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&john_address, &[]),
+            ExecuteMsg::UpdatePrimaryAlias {
+                token_id: "johnone".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdatePrimaryAliasPolicy {
+                policy: PrimaryAliasPolicy::None,
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::TransferNft {
+                recipient: john_address.clone(),
+                token_id: "jeffthree".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &john_address).unwrap(),
+            "johnone"
+        );
+
+        // CHECK: with no fallback, jeff has no alias
+        // even though he still owns jefffour
+        let err = primary_alias_of(deps.as_ref(), &jeff_address).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn contract_reverse_record() {
+        use cosmwasm_std::{
+            ContractInfoResponse as WasmContractInfoResponse, ContractResult, SystemError,
+            SystemResult, WasmQuery,
+        };
+
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr }
+                if contract_addr == "juno1vwr8z00ty7mqnk4dtchr9mn9j96nuh6w0verng" =>
+            {
+                let mut contract_info = WasmContractInfoResponse::new(1, "creator");
+                contract_info.admin = Some("jeff-vader".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&contract_info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let dao_address = String::from("juno1vwr8z00ty7mqnk4dtchr9mn9j96nuh6w0verng");
+
+        let dao_meta = Metadata {
+            contract_address: Some(dao_address.clone()),
+            ..Metadata::default()
+        };
+        for (token_id, meta) in [("jeffvader", Metadata::default()), ("jeffsdao", dao_meta)] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: meta,
+            });
+            entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+        }
+
+        let set_record_msg = ExecuteMsg::SetContractReverseRecord {
+            contract: dao_address.clone(),
+            token_id: "jeffsdao".to_string(),
+        };
+
+        // CHECK: only the contract's admin can set it
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john-vader", &[]),
+            set_record_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: and only to a name that points at the contract
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::SetContractReverseRecord {
+                contract: dao_address.clone(),
+                token_id: "jeffvader".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ContractAddressMismatch {});
+
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), set_record_msg).unwrap();
+
+        // CHECK: the contract now resolves to the name
+        let primary_alias_query = QueryMsg::PrimaryAlias {
+            address: dao_address.clone(),
+        };
+        let res: PrimaryAliasResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), primary_alias_query.clone()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.username, "jeffsdao");
+
+        let res: PrimaryAliasesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliases {
+                    addresses: vec![dao_address.clone()],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.aliases[0].source, PrimaryAliasSource::ContractRecord);
+
+        // CHECK: renaming the name takes the record with it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::Rename {
+                token_id: "jeffsdao".to_string(),
+                new_token_id: "jeffsnewdao".to_string(),
+            },
+        )
+        .unwrap();
+        let res: PrimaryAliasResponse = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), primary_alias_query.clone()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.username, "jeffsnewdao");
+
+        // CHECK: once the name no longer points at the contract
+        // the record is ignored
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: "jeffsnewdao".to_string(),
+                metadata: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        let err = entry::query(deps.as_ref(), mock_env(), primary_alias_query.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string()
+            }
+        );
+
+        // CHECK: a name that is sold loses the record
+        // even if the buyer points it back at the contract
+        let dao_meta = Metadata {
+            contract_address: Some(dao_address.clone()),
+            ..Metadata::default()
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: "jeffsnewdao".to_string(),
+                metadata: dao_meta.clone(),
+            }),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::SetContractReverseRecord {
+                contract: dao_address.clone(),
+                token_id: "jeffsnewdao".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "john-vader".to_string(),
+                token_id: "jeffsnewdao".to_string(),
+            },
+        )
+        .unwrap();
+        let dao_addr = Addr::unchecked(&dao_address);
+        assert!(!CONTRACT_REVERSE_RECORDS.has(&deps.storage, &dao_addr));
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john-vader", &[]),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: "jeffsnewdao".to_string(),
+                metadata: dao_meta.clone(),
+            }),
+        )
+        .unwrap();
+        let err = entry::query(deps.as_ref(), mock_env(), primary_alias_query.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string()
+            }
+        );
+
+        // CHECK: a record left pointing at a name that changed hands is ignored
+        CONTRACT_REVERSE_RECORDS
+            .save(
+                &mut deps.storage,
+                &dao_addr,
+                &ContractReverseRecord {
+                    token_id: "jeffsnewdao".to_string(),
+                    owner: Addr::unchecked(&jeff_address),
+                },
+            )
+            .unwrap();
+        let err = entry::query(deps.as_ref(), mock_env(), primary_alias_query.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string()
+            }
+        );
+
+        // CHECK: burning the name removes the record
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "jeffsotherdao".to_string(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: dao_meta,
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::SetContractReverseRecord {
+                contract: dao_address.clone(),
+                token_id: "jeffsotherdao".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::Burn {
+                token_id: "jeffsotherdao".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(!CONTRACT_REVERSE_RECORDS.has(&deps.storage, &dao_addr));
+    }
+
+    #[test]
+    fn received_name_does_not_replace_resolved_alias() {
+        use cosmwasm_std::{
+            ContractInfoResponse as WasmContractInfoResponse, ContractResult, SystemError,
+            SystemResult, WasmQuery,
+        };
+
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr }
+                if contract_addr == "juno1vwr8z00ty7mqnk4dtchr9mn9j96nuh6w0verng" =>
+            {
+                let mut contract_info = WasmContractInfoResponse::new(1, "creator");
+                contract_info.admin = Some("jeff-vader".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&contract_info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let bob_address = String::from("bob-address");
+        let carol_address = String::from("carol-address");
+        let mallory_address = String::from("mallory-address");
+        let mallory = mock_info(&mallory_address, &[]);
+        let dao_address = String::from("juno1vwr8z00ty7mqnk4dtchr9mn9j96nuh6w0verng");
+
+        let dao_meta = Metadata {
+            contract_address: Some(dao_address.clone()),
+            ..Metadata::default()
+        };
+        for (token_id, owner, meta) in [
+            ("bob", &bob_address, Metadata::default()),
+            ("jeffsdao", &jeff_address, dao_meta),
+            ("bobisascammer", &mallory_address, Metadata::default()),
+            ("carolisascammer", &mallory_address, Metadata::default()),
+            ("daoisascammer", &mallory_address, Metadata::default()),
+        ] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: owner.clone(),
+                token_uri: None,
+                extension: meta,
+            });
+            entry::execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), mint_msg).unwrap();
+        }
+
+        let primary_alias_of = |deps: Deps, address: &str| -> StdResult<String> {
+            let res: PrimaryAliasResponse = from_binary(&entry::query(
+                deps,
+                mock_env(),
+                QueryMsg::PrimaryAlias {
+                    address: address.to_string(),
+                },
+            )?)?;
+            Ok(res.username)
+        };
+
+        // CHECK: bob resolves to his first token
+        // and a name sent to him by someone else doesn't change that
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &bob_address).unwrap(),
+            "bob"
+        );
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mallory.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: bob_address.clone(),
+                token_id: "bobisascammer".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &bob_address).unwrap(),
+            "bob"
+        );
+
+        // CHECK: nor does bob get an explicit alias he never chose
+        let res: PrimaryAliasesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PrimaryAliases {
+                    addresses: vec![bob_address.clone()],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.aliases[0].source, PrimaryAliasSource::FirstToken);
+
+        // CHECK: an address that opted out of fallbacks is left without an alias
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&carol_address, &[]),
+            ExecuteMsg::UpdatePrimaryAliasPolicy {
+                policy: PrimaryAliasPolicy::None,
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mallory.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: carol_address.clone(),
+                token_id: "carolisascammer".to_string(),
+            },
+        )
+        .unwrap();
+        let err = primary_alias_of(deps.as_ref(), &carol_address).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string()
+            }
+        );
+
+        // CHECK: a contract resolving through its reverse record
+        // keeps that name when another one is sent to it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::SetContractReverseRecord {
+                contract: dao_address.clone(),
+                token_id: "jeffsdao".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mallory,
+            ExecuteMsg::SendNft {
+                contract: dao_address.clone(),
+                token_id: "daoisascammer".to_string(),
+                msg: to_binary("yolo").unwrap(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            primary_alias_of(deps.as_ref(), &dao_address).unwrap(),
+            "jeffsdao"
+        );
+    }
+
+    #[test]
+    fn all_primary_aliases() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for (token_id, owner) in [
+            ("alice", "addr-a"),
+            ("alicealt", "addr-a"),
+            ("bob", "addr-b"),
+            ("carol", "addr-c"),
+        ] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            });
+            entry::execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), mint_msg).unwrap();
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(owner, &[]),
+                ExecuteMsg::UpdatePrimaryAlias {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+        }
+
+        let all_aliases = |deps: Deps, start_after: Option<&str>| -> Vec<PrimaryAliasRecord> {
+            let res: AllPrimaryAliasesResponse = from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::AllPrimaryAliases {
+                        start_after: start_after.map(String::from),
+                        limit: Some(2),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.aliases
+        };
+        let addresses_for = |deps: Deps, token_id: &str| -> Vec<String> {
+            let res: AddressesResponse = from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::PrimaryAliasAddresses {
+                        token_id: token_id.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.addresses
+        };
+
+        // CHECK: pages through every explicit alias by address
+        assert_eq!(
+            all_aliases(deps.as_ref(), None),
+            vec![
+                PrimaryAliasRecord {
+                    address: "addr-a".to_string(),
+                    username: "alicealt".to_string(),
+                },
+                PrimaryAliasRecord {
+                    address: "addr-b".to_string(),
+                    username: "bob".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            all_aliases(deps.as_ref(), Some("addr-b")),
+            vec![PrimaryAliasRecord {
+                address: "addr-c".to_string(),
+                username: "carol".to_string(),
+            }]
+        );
+
+        // CHECK: the index follows changes of alias
+        assert_eq!(addresses_for(deps.as_ref(), "alice"), Vec::<String>::new());
+        assert_eq!(addresses_for(deps.as_ref(), "alicealt"), vec!["addr-a"]);
+
+        // CHECK: and the alias leaving on transfer
+        // addr-d has no alias, so picks bob up as theirs
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr-b", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: "addr-d".to_string(),
+                token_id: "bob".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(addresses_for(deps.as_ref(), "bob"), vec!["addr-d"]);
+        assert_eq!(
+            all_aliases(deps.as_ref(), Some("addr-a")),
+            vec![
+                PrimaryAliasRecord {
+                    address: "addr-c".to_string(),
+                    username: "carol".to_string(),
+                },
+                PrimaryAliasRecord {
+                    address: "addr-d".to_string(),
+                    username: "bob".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn text_record_default_caps() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+
+        let set_text_record = |key: &str, value: &str| ExecuteMsg::SetTextRecord {
+            token_id: token_id.clone(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        // CHECK: with no limits set, keys and values are still capped
+        let long_key = "a".repeat(MAX_TEXT_RECORD_KEY_LENGTH as usize + 1);
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            set_text_record(&long_key, "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TextRecordKeyTooLong {});
+
+        let long_value = "a".repeat(MAX_TEXT_RECORD_VALUE_LENGTH as usize + 1);
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            set_text_record("com.github", &long_value),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TextRecordValueTooLong {});
+
+        // CHECK: and so is the number of records
+        for i in 0..MAX_TEXT_RECORDS {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                jeff.clone(),
+                set_text_record(&format!("key{}", i), "jeffvader"),
+            )
+            .unwrap();
+        }
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            set_text_record("onemore", "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TextRecordLimitReached {});
+
+        // CHECK: the admin can't raise any limit above its default
+        for limits in [
+            TextRecordLimits {
+                max_key_length: Some(MAX_TEXT_RECORD_KEY_LENGTH + 1),
+                max_value_length: None,
+                max_records: None,
+            },
+            TextRecordLimits {
+                max_key_length: None,
+                max_value_length: Some(MAX_TEXT_RECORD_VALUE_LENGTH + 1),
+                max_records: None,
+            },
+            TextRecordLimits {
+                max_key_length: None,
+                max_value_length: None,
+                max_records: Some(MAX_TEXT_RECORDS + 1),
+            },
+        ] {
+            let err = entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                ExecuteMsg::UpdateTextRecordLimits(limits),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::TextRecordLimitsTooHigh {});
+        }
+
+        // CHECK: but can set them to exactly the defaults
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateTextRecordLimits(TextRecordLimits {
+                max_key_length: Some(MAX_TEXT_RECORD_KEY_LENGTH),
+                max_value_length: Some(MAX_TEXT_RECORD_VALUE_LENGTH),
+                max_records: Some(MAX_TEXT_RECORDS),
+            }),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn text_records() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let limits = TextRecordLimits {
+            max_key_length: Some(10),
+            max_value_length: Some(20),
+            max_records: Some(2),
+        };
+
+        // CHECK: only the admin can set limits
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::UpdateTextRecordLimits(limits.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateTextRecordLimits(limits.clone()),
+        )
+        .unwrap();
+        let limits_res: TextRecordLimits = from_binary(
+            &entry::query(deps.as_ref(), mock_env(), QueryMsg::TextRecordLimits {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(limits_res, limits);
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata {
+                twitter_id: Some(String::from("@jeff_vader")),
+                external_url: Some(String::from("https://example.com")),
+                ..Metadata::default()
+            },
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+
+        let set_text_record = |key: &str, value: &str| ExecuteMsg::SetTextRecord {
+            token_id: token_id.clone(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        // CHECK: only the owner can set a record
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john-vader", &[]),
+            set_text_record("com.github", "jeffvader"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: keys and values are validated
+        for (key, value, expected_err) in [
+            (
+                "Com.GitHub",
+                "jeffvader",
+                ContractError::InvalidTextRecordKey {},
+            ),
+            ("", "jeffvader", ContractError::InvalidTextRecordKey {}),
+            (
+                "com.twitter",
+                "@jeff",
+                ContractError::ReservedTextRecordKey {},
+            ),
+            (
+                "com.example",
+                "jeffvader",
+                ContractError::TextRecordKeyTooLong {},
+            ),
+            (
+                "com.github",
+                "jeffvader-but-much-longer",
+                ContractError::TextRecordValueTooLong {},
+            ),
+        ] {
+            let err = entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                jeff.clone(),
+                set_text_record(key, value),
+            )
+            .unwrap_err();
+            assert_eq!(err, expected_err);
+        }
+
+        for (key, value) in [
+            ("com.github", "jeff"),
+            ("xyz.lens", "jeffvader.lens"),
+            ("com.github", "jeffvader"),
+        ] {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                jeff.clone(),
+                set_text_record(key, value),
+            )
+            .unwrap();
+        }
+
+        // CHECK: overwriting doesn't count towards the limit, but a new key does
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            set_text_record("a.b", "c"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TextRecordLimitReached {});
+
+        // CHECK: stored and well-known keys can both be read
+        let text_record = |deps: Deps, key: &str| -> StdResult<TextRecordResponse> {
+            from_binary(&entry::query(
+                deps,
+                mock_env(),
+                QueryMsg::TextRecord {
+                    token_id: "jeffvader".to_string(),
+                    key: key.to_string(),
+                },
+            )?)
+        };
+        assert_eq!(
+            text_record(deps.as_ref(), "com.github").unwrap().value,
+            "jeffvader"
+        );
+        assert_eq!(
+            text_record(deps.as_ref(), "com.twitter").unwrap().value,
+            "@jeff_vader"
+        );
+        assert!(text_record(deps.as_ref(), "email").is_err());
+
+        // CHECK: and are listed together in key order
+        let text_records = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+            let res: TextRecordsResponse = from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::TextRecords {
+                        token_id: "jeffvader".to_string(),
+                        start_after: start_after.map(String::from),
+                        limit,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.records
+                .into_iter()
+                .map(|record| record.key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            text_records(deps.as_ref(), None, None),
+            vec!["com.github", "com.twitter", "url", "xyz.lens"]
+        );
+        assert_eq!(
+            text_records(deps.as_ref(), Some("com.twitter"), Some(1)),
+            vec!["url"]
+        );
+        assert_eq!(
+            text_records(deps.as_ref(), Some("url"), None),
+            vec!["xyz.lens"]
+        );
+
+        // CHECK: records can be removed
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::RemoveTextRecord {
+                token_id: token_id.clone(),
+                key: "com.github".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(text_record(deps.as_ref(), "com.github").is_err());
+
+        // CHECK: and are cleared with the rest of the metadata on transfer
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::TransferNft {
+                recipient: "john-vader".to_string(),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            text_records(deps.as_ref(), None, None),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn address_records() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+
+        let osmosis = Chain::Bech32 {
+            prefix: "osmo".to_string(),
+        };
+        let osmo_address = "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw".to_string();
+        let ethereum = Chain::Evm { chain_id: 1 };
+        let eth_address = "0x52908400098527886E0F7030069857D2E4169EE7".to_string();
+
+        let set_address_record = |chain: &Chain, address: &str| ExecuteMsg::SetAddressRecord {
+            token_id: token_id.clone(),
+            chain: chain.clone(),
+            address: address.to_string(),
+        };
+
+        // CHECK: only the owner can set an address
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john-vader", &[]),
+            set_address_record(&osmosis, &osmo_address),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: addresses are checked against the chain
+        for (chain, address, expected_err) in [
+            (
+                osmosis.clone(),
+                "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu",
+                ContractError::InvalidChainAddress {},
+            ),
+            (
+                Chain::Bech32 {
+                    prefix: "".to_string(),
+                },
+                "1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu",
+                ContractError::InvalidBech32Prefix {},
+            ),
+            (
+                Chain::Bech32 {
+                    prefix: "Osmo".to_string(),
+                },
+                &osmo_address,
+                ContractError::InvalidBech32Prefix {},
+            ),
+            (
+                ethereum.clone(),
+                "0x52908400098527886E0F7030069857D2E4169EE",
+                ContractError::InvalidChainAddress {},
+            ),
+            (
+                Chain::Slip44 { coin_type: 60 },
+                &osmo_address,
+                ContractError::InvalidChainAddress {},
+            ),
+            (
+                Chain::Slip44 { coin_type: 118 },
+                &osmo_address,
+                ContractError::InvalidChainAddress {},
+            ),
+        ] {
+            let err = entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                jeff.clone(),
+                set_address_record(&chain, address),
+            )
+            .unwrap_err();
+            assert_eq!(err, expected_err);
+        }
+
+        for (chain, address) in [
+            (&osmosis, osmo_address.as_str()),
+            (&ethereum, eth_address.as_str()),
+            (
+                &Chain::Slip44 { coin_type: 0 },
+                "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            ),
+        ] {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                jeff.clone(),
+                set_address_record(chain, address),
+            )
+            .unwrap();
+        }
+
+        let address_of_on_chain = |deps: Deps, chain: &Chain| -> StdResult<AddressRecord> {
+            from_binary(&entry::query(
+                deps,
+                mock_env(),
+                QueryMsg::AddressOfOnChain {
+                    token_id: "jeffvader".to_string(),
+                    chain: chain.clone(),
+                },
+            )?)
+        };
+
+        // CHECK: the name resolves on each chain
+        assert_eq!(
+            address_of_on_chain(deps.as_ref(), &osmosis).unwrap(),
+            AddressRecord {
+                chain: osmosis.clone(),
+                address: osmo_address,
+            }
+        );
+        assert_eq!(
+            address_of_on_chain(deps.as_ref(), &ethereum)
+                .unwrap()
+                .address,
+            eth_address
+        );
+        assert!(address_of_on_chain(deps.as_ref(), &Chain::Evm { chain_id: 10 }).is_err());
+
+        // CHECK: records can be removed
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::RemoveAddressRecord {
+                token_id: token_id.clone(),
+                chain: ethereum.clone(),
+            },
+        )
+        .unwrap();
+        assert!(address_of_on_chain(deps.as_ref(), &ethereum).is_err());
+
+        // CHECK: and are cleared on transfer
+        // so funds don't go to the old owner
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::TransferNft {
+                recipient: "john-vader".to_string(),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+        assert!(address_of_on_chain(deps.as_ref(), &osmosis).is_err());
+    }
+
+    #[test]
+    fn derived_addresses() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: String::from(MINTER),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        let info = mock_info("creator", &[]);
+        entry::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+
+        let osmo_address = "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw".to_string();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::SetAddressRecord {
+                token_id: token_id.clone(),
+                chain: Chain::Bech32 {
+                    prefix: "osmo".to_string(),
+                },
+                address: osmo_address.clone(),
+            },
+        )
+        .unwrap();
+
+        let res: DerivedAddressesResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::DerivedAddresses {
+                    token_id: token_id.clone(),
+                    prefixes: vec!["osmo".to_string(), "juno".to_string()],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // CHECK: an explicit record wins
+        assert_eq!(res.addresses[0].prefix, "osmo");
+        assert_eq!(res.addresses[0].address, osmo_address);
+        assert_eq!(res.addresses[0].source, AddressSource::Explicit);
+
+        // CHECK: otherwise the owner's address bytes are re-encoded
+        assert_eq!(res.addresses[1].prefix, "juno");
+        assert_eq!(res.addresses[1].source, AddressSource::Derived);
+        let (hrp, address_bytes) = bech32::decode(&res.addresses[1].address).unwrap();
+        assert_eq!(hrp, "juno");
+        assert_eq!(
+            address_bytes,
+            deps.api
+                .addr_canonicalize(&jeff_address)
+                .unwrap()
+                .as_slice()
+                .to_vec()
+        );
+
+        // CHECK: prefixes are checked
+        let err = entry::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DerivedAddresses {
+                token_id: token_id.clone(),
+                prefixes: vec!["Juno".to_string()],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("Invalid bech32 prefix: Juno"));
+
+        // CHECK: at most 30 prefixes can be looked up at once
+        let derived_count = |deps: Deps, count: usize| -> StdResult<usize> {
+            let res: DerivedAddressesResponse = from_binary(&entry::query(
+                deps,
+                mock_env(),
+                QueryMsg::DerivedAddresses {
+                    token_id: token_id.clone(),
+                    prefixes: vec!["osmo".to_string(); count],
+                },
+            )?)?;
+            Ok(res.addresses.len())
+        };
+        assert_eq!(derived_count(deps.as_ref(), 30).unwrap(), 30);
+        let err = derived_count(deps.as_ref(), 31).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Too many prefixes, at most 30 can be looked up at once")
+        );
+    }
+
+    #[test]
+    fn patch_metadata() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let meta = Metadata {
+            email: Some(String::from("jeff@example.com")),
+            public_bio: Some(String::from("jeff vader")),
+            twitter_id: Some(String::from("@jeff_vader")),
+            ..Metadata::default()
+        };
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: jeff_address.clone(),
+            token_uri: None,
+            extension: meta.clone(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg).unwrap();
+
+        let revision_of = |deps: Deps| -> u64 {
+            let res: MetadataRevisionResponse = from_binary(
+                &entry::query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::MetadataRevision {
+                        token_id: "jeffvader".to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.revision
+        };
+        assert_eq!(revision_of(deps.as_ref()), 0);
+
+        // CHECK: fields can be set or cleared, and the rest are left alone
+        let patch_msg: ExecuteMsg = from_slice(
+            br#"{"patch_metadata":{"token_id":"jeffvader","expected_revision":0,"public_bio":{"set":"jeff vader, again"},"email":"clear"}}"#,
+        )
+        .unwrap();
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), patch_msg).unwrap();
+
+        let token = contract.tokens.load(&deps.storage, &token_id).unwrap();
+        assert_eq!(
+            token.extension,
+            Metadata {
+                email: None,
+                public_bio: Some(String::from("jeff vader, again")),
+                ..meta
+            }
+        );
+        assert_eq!(revision_of(deps.as_ref()), 1);
+
+        // CHECK: a patch based on an old revision is rejected
+        let stale_patch_msg = ExecuteMsg::PatchMetadata(PatchMetadataMsg {
+            token_id: token_id.clone(),
+            expected_revision: Some(0),
+            twitter_id: Some(FieldUpdate::Clear),
+            ..PatchMetadataMsg::default()
+        });
+        let err =
+            entry::execute(deps.as_mut(), mock_env(), jeff.clone(), stale_patch_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MetadataRevisionMismatch {
+                expected: 0,
+                current: 1
+            }
+        );
+
+        // CHECK: only the owner can patch
+        let patch_msg = ExecuteMsg::PatchMetadata(PatchMetadataMsg {
+            token_id: token_id.clone(),
+            twitter_id: Some(FieldUpdate::Clear),
+            ..PatchMetadataMsg::default()
+        });
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john-vader", &[]),
+            patch_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // CHECK: the revision is optional
+        entry::execute(deps.as_mut(), mock_env(), jeff.clone(), patch_msg).unwrap();
+        let token = contract.tokens.load(&deps.storage, &token_id).unwrap();
+        assert_eq!(token.extension.twitter_id, None);
+        assert_eq!(revision_of(deps.as_ref()), 2);
+
+        // CHECK: a full update bumps the revision too
+        let update_msg = ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+            token_id,
+            metadata: Metadata::default(),
+        });
+        entry::execute(deps.as_mut(), mock_env(), jeff, update_msg).unwrap();
+        assert_eq!(revision_of(deps.as_ref()), 3);
+    }
+
+    #[test]
+    fn metadata_field_validators() {
+        assert_eq!(url_is_valid("https://example.com/jeff.png"), true);
+        assert_eq!(
+            url_is_valid("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"),
+            true
+        );
+        assert_eq!(url_is_valid("ar://jeffvader"), true);
+        assert_eq!(url_is_valid("http://example.com"), false);
+        assert_eq!(url_is_valid("https://"), false);
+        assert_eq!(url_is_valid("https://example.com/jeff vader.png"), false);
+
+        assert_eq!(email_is_valid("jeff@example.com"), true);
+        assert_eq!(email_is_valid("jeff.vader+ships@mail.example.co"), true);
+        assert_eq!(email_is_valid("jeff"), false);
+        assert_eq!(email_is_valid("jeff@localhost"), false);
+        assert_eq!(email_is_valid("jeff..vader@example.com"), false);
+        assert_eq!(email_is_valid("jeff@-example.com"), false);
+        assert_eq!(email_is_valid("jeff vader@example.com"), false);
+
+        assert_eq!(twitter_handle_is_valid("@jeff_vader"), true);
+        assert_eq!(twitter_handle_is_valid("jeffvader"), true);
+        assert_eq!(twitter_handle_is_valid("@jeff-vader"), false);
+        assert_eq!(twitter_handle_is_valid("@"), false);
+        assert_eq!(twitter_handle_is_valid("@jeff_vader_the_third"), false);
+
+        assert_eq!(discord_handle_is_valid("jeffvader#0001"), true);
+        assert_eq!(discord_handle_is_valid("jeff.vader"), true);
+        assert_eq!(discord_handle_is_valid("jeffvader#001"), false);
+        assert_eq!(discord_handle_is_valid("JeffVader"), false);
+        assert_eq!(discord_handle_is_valid("jeff..vader"), false);
+
+        assert_eq!(telegram_handle_is_valid("@jeff_vader"), true);
+        assert_eq!(telegram_handle_is_valid("jeff"), false);
+        assert_eq!(telegram_handle_is_valid("_jeffvader"), false);
+
+        assert_eq!(keybase_handle_is_valid("jeffvader"), true);
+        assert_eq!(keybase_handle_is_valid("JeffVader"), false);
+        assert_eq!(keybase_handle_is_valid("j"), false);
+    }
+
+    #[test]
+    fn metadata_validation() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+
+        let jeff_address = String::from("jeff-vader");
+        let jeff = mock_info(&jeff_address, &[]);
+        let token_id = "jeffvader".to_string();
+        let mint_msg = |extension: Metadata| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "jeffvader".to_string(),
+                owner: "jeff-vader".to_string(),
+                token_uri: None,
+                extension,
+            })
+        };
+
+        // CHECK: a mint with an invalid field is rejected
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            mint_msg(Metadata {
+                email: Some(String::from("jeff-at-example.com")),
+                ..Metadata::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidEmail {});
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            mint_msg(Metadata {
+                image: Some(String::from("http://example.com/jeff.png")),
+                ..Metadata::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidImageUrlScheme {});
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            mint_msg(Metadata {
+                public_name: Some("jeff".repeat(20)),
+                ..Metadata::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PublicNameTooLong {});
+
+        // CHECK: every capped field has its own error
+        let long_url = format!("https://example.com/{}", "a".repeat(512));
+        let long_address = format!("juno1{}", "q".repeat(128));
+        for (meta, expected_err) in [
+            (
+                Metadata {
+                    image: Some(long_url.clone()),
+                    ..Metadata::default()
+                },
+                ContractError::ImageUrlTooLong {},
+            ),
+            (
+                Metadata {
+                    external_url: Some(long_url),
+                    ..Metadata::default()
+                },
+                ContractError::ExternalUrlTooLong {},
+            ),
+            (
+                Metadata {
+                    email: Some(format!("{}@example.com", "jeff.".repeat(60))),
+                    ..Metadata::default()
+                },
+                ContractError::EmailTooLong {},
+            ),
+            (
+                Metadata {
+                    public_bio: Some("jeff".repeat(200)),
+                    ..Metadata::default()
+                },
+                ContractError::BioTooLong {},
+            ),
+            (
+                Metadata {
+                    validator_operator_address: Some(long_address.clone()),
+                    ..Metadata::default()
+                },
+                ContractError::ValidatorOperatorAddressTooLong {},
+            ),
+            (
+                Metadata {
+                    contract_address: Some(long_address),
+                    ..Metadata::default()
+                },
+                ContractError::ContractAddressTooLong {},
+            ),
+            (
+                Metadata {
+                    pgp_public_key: Some("a".repeat(8 * 1024 + 1)),
+                    ..Metadata::default()
+                },
+                ContractError::PgpPublicKeyTooLong {},
+            ),
+        ] {
+            let err = entry::execute(deps.as_mut(), mock_env(), jeff.clone(), mint_msg(meta))
+                .unwrap_err();
+            assert_eq!(err, expected_err);
+        }
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            mint_msg(Metadata {
+                contract_address: Some(String::from("jeff-vader-contract")),
+                ..Metadata::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidContractAddress {});
+
+        let meta = Metadata {
+            image: Some(String::from(
+                "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            )),
+            email: Some(String::from("jeff@example.com")),
+            external_url: Some(String::from("https://example.com/jeff")),
+            twitter_id: Some(String::from("@jeff_vader")),
+            discord_id: Some(String::from("jeff.vader")),
+            telegram_id: Some(String::from("@jeff_vader")),
+            keybase_id: Some(String::from("jeffvader")),
+            validator_operator_address: Some(String::from(
+                "junovaloper1lq40xgtqh3f3zt9prz4m74l6dlk506usrp7p6r",
+            )),
+            ..Metadata::default()
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            mint_msg(meta.clone()),
+        )
+        .unwrap();
+
+        // CHECK: a path is validated the same way
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: "vehicles".to_string(),
+                owner: jeff_address.clone(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(token_id.clone()),
+                    keybase_id: Some(String::from("Jeff Vader")),
+                    ..Metadata::default()
+                },
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidKeybaseHandle {});
+
+        // CHECK: an update can no longer store an invalid PGP key
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: token_id.clone(),
+                metadata: Metadata {
+                    pgp_public_key: Some(String::from("not a pgp key")),
+                    ..meta.clone()
+                },
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPgpPublicKey {});
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::UpdateMetadata(UpdateMetadataMsg {
+                token_id: token_id.clone(),
+                metadata: Metadata {
+                    validator_operator_address: Some(String::from("jeff-vader-validator")),
+                    ..meta.clone()
+                },
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidValidatorOperatorAddress {});
+
+        // CHECK: so can a patch
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff.clone(),
+            ExecuteMsg::PatchMetadata(PatchMetadataMsg {
+                token_id: token_id.clone(),
+                telegram_id: Some(FieldUpdate::Set(String::from("jeff"))),
+                ..PatchMetadataMsg::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTelegramHandle {});
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            jeff,
+            ExecuteMsg::PatchMetadata(PatchMetadataMsg {
+                token_id: token_id.clone(),
+                external_url: Some(FieldUpdate::Set(String::from("javascript:alert(1)"))),
+                twitter_id: Some(FieldUpdate::Set(String::from("@jeff-vader"))),
+                ..PatchMetadataMsg::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidExternalUrlScheme {});
+
+        // nothing was written
+        let token = contract.tokens.load(&deps.storage, &token_id).unwrap();
+        assert_eq!(token.extension, meta);
+    }
+
+    #[test]
+    fn transferred_paths_burned_with_root() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+        let alice = mock_info("alice-address", &[]);
+        let bob = mock_info("bob-address", &[]);
+        let carol = mock_info("carol-address", &[]);
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: MINTER.to_string(),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), init_msg).unwrap();
+
+        let mint_msg = |owner: &str| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "alice".to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            })
+        };
+        let path_mint_msg = |owner: &str, parent: &str, path: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            mint_msg("alice-address"),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice", "x"),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice::x", "y"),
+        )
+        .unwrap();
+
+        // alice sells alice::x to bob
+        // the path she still has under it goes
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "bob-address".to_string(),
+                token_id: "alice::x".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(contract.num_tokens(deps.as_ref()).unwrap().count, 2);
+
+        // CHECK: the path is still under alice
+        let children_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "alice".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(children_res.tokens, vec!["alice::x".to_string()]);
+
+        // CHECK: burning alice takes bob's path with it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice,
+            ExecuteMsg::Burn {
+                token_id: "alice".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(contract.num_tokens(deps.as_ref()).unwrap().count, 0);
+        assert_eq!(
+            PATH_COUNTS
+                .may_load(&deps.storage, "alice")
+                .unwrap()
+                .unwrap_or_default(),
+            0
+        );
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob,
+            path_mint_msg("bob-address", "alice::x", "z"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+
+        // CHECK: so whoever claims alice next gets a clean namespace
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            carol.clone(),
+            mint_msg("carol-address"),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            carol,
+            path_mint_msg("carol-address", "alice", "x"),
+        )
+        .unwrap();
+        assert_eq!(PATH_COUNTS.load(&deps.storage, "alice").unwrap(), 1);
+    }
+
+    #[test]
+    fn pending_burn_locks_paths_below() {
+        let mut deps = mock_dependencies();
+        let alice = mock_info("alice-address", &[]);
+        let bob = mock_info("bob-address", &[]);
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: MINTER.to_string(),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), init_msg).unwrap();
+
+        let path_mint_msg = |owner: &str, parent: &str, path: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "alice".to_string(),
+                owner: "alice-address".to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice", "x"),
+        )
+        .unwrap();
+        for i in 0..=PATH_BURN_BATCH_SIZE {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                alice.clone(),
+                path_mint_msg("alice-address", "alice::x", &format!("p{:02}", i)),
+            )
+            .unwrap();
+        }
+
+        // too many paths under alice::x to burn when it is sold
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "bob-address".to_string(),
+                token_id: "alice::x".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "paths_burned" && attr.value == "false"));
+
+        // CHECK: nothing can be minted under it, at any depth, until they are gone
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob.clone(),
+            path_mint_msg("bob-address", "alice::x", "z"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice::x::p50", "z"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+
+        // CHECK: only alice's paths were burned, and bob can mint once they are gone
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::ContinueBurnPaths {
+                token_id: "alice::x".to_string(),
+            },
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob.clone(),
+            path_mint_msg("bob-address", "alice::x", "z"),
+        )
+        .unwrap();
+
+        // now too many paths under alice to burn in one go
+        for i in 0..=PATH_BURN_BATCH_SIZE {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                alice.clone(),
+                path_mint_msg("alice-address", "alice", &format!("p{:02}", i)),
+            )
+            .unwrap();
+        }
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice,
+            ExecuteMsg::Burn {
+                token_id: "alice".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: bob can't move his path out from under a pending burn
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob,
+            ExecuteMsg::TransferNft {
+                recipient: "random-guy".to_string(),
+                token_id: "alice::x".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+    }
+
+    #[test]
+    fn pending_burn_follows_moves_and_renames() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+        let alice = mock_info("alice-address", &[]);
+        let bob = mock_info("bob-address", &[]);
+
+        let init_msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            native_denom: "uatom".to_string(),
+            native_decimals: 6,
+            token_cap: None,
+            base_mint_fee: None,
+            burn_percentage: None,
+            short_name_surcharge: None,
+            admin_address: MINTER.to_string(),
+            username_length_cap: None,
+            registration_model: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), init_msg).unwrap();
+
+        let path_mint_msg = |owner: &str, parent: &str, path: &str| {
+            ExecuteMsg::MintPath(MintMsg {
+                token_id: path.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Metadata {
+                    parent_token_id: Some(parent.to_string()),
+                    ..Metadata::default()
+                },
+            })
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "alice".to_string(),
+                owner: "alice-address".to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice", "a"),
+        )
+        .unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            path_mint_msg("alice-address", "alice::a", "b"),
+        )
+        .unwrap();
+        for i in 0..=PATH_BURN_BATCH_SIZE {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                alice.clone(),
+                path_mint_msg("alice-address", "alice::a::b", &format!("p{:02}", i)),
+            )
+            .unwrap();
+        }
+
+        // too many paths under alice::a::b to burn when it is sold
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "bob-address".to_string(),
+                token_id: "alice::a::b".to_string(),
+            },
+        )
+        .unwrap();
+
+        // alice moves the path above it
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            ExecuteMsg::MovePath {
+                token_id: "alice::a".to_string(),
+                new_parent: None,
+                new_name: Some("c".to_string()),
+            },
+        )
+        .unwrap();
+
+        // CHECK: the pending burn moved with it, so it is still locked
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "random-guy".to_string(),
+                token_id: "alice::c::b".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+
+        // CHECK: and it is finished under the new id, not the old one
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::ContinueBurnPaths {
+                token_id: "alice::a::b".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPathBurnPending {});
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::ContinueBurnPaths {
+                token_id: "alice::c::b".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            PARENT_INDEX
+                .prefix("alice::c::b")
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .count(),
+            0
+        );
+
+        // now bob has too many paths under it to burn when he sells it
+        for i in 0..=PATH_BURN_BATCH_SIZE {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                bob.clone(),
+                path_mint_msg("bob-address", "alice::c::b", &format!("q{:02}", i)),
+            )
+            .unwrap();
+        }
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob.clone(),
+            ExecuteMsg::TransferNft {
+                recipient: "random-guy".to_string(),
+                token_id: "alice::c::b".to_string(),
+            },
+        )
+        .unwrap();
+
+        // alice renames the root
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            alice,
+            ExecuteMsg::Rename {
+                token_id: "alice".to_string(),
+                new_token_id: "alicia".to_string(),
+            },
+        )
+        .unwrap();
+
+        // CHECK: the pending burn was renamed too, and still locks what is left
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            bob,
+            path_mint_msg("bob-address", "alicia::c::b::q50", "z"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PathBurnPending {});
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random-guy", &[]),
+            ExecuteMsg::ContinueBurnPaths {
+                token_id: "alicia::c::b".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            PARENT_INDEX
+                .prefix("alicia::c::b")
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .count(),
+            0
+        );
+        assert_eq!(
+            contract
+                .tokens
+                .load(&deps.storage, "alicia::c::b")
+                .unwrap()
+                .owner,
+            "random-guy"
+        );
+    }
+
+    #[test]
+    fn rename_with_too_many_paths() {
+        let mut deps = mock_dependencies();
+        let contract = setup_contract(deps.as_mut());
+        let allowed = mock_info(MINTER, &[]);
+
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::Mint(MintMsg {
+                token_id: "jeffvader".to_string(),
+                owner: MINTER.to_string(),
+                token_uri: None,
+                extension: Metadata::default(),
+            }),
+        )
+        .unwrap();
+        for i in 0..=PATH_MOVE_BATCH_SIZE {
+            entry::execute(
+                deps.as_mut(),
+                mock_env(),
+                allowed.clone(),
+                ExecuteMsg::MintPath(MintMsg {
+                    token_id: format!("p{:02}", i),
+                    owner: MINTER.to_string(),
+                    token_uri: None,
+                    extension: Metadata {
+                        parent_token_id: Some("jeffvader".to_string()),
+                        ..Metadata::default()
+                    },
+                }),
+            )
+            .unwrap();
+        }
+
+        let rename_msg = ExecuteMsg::Rename {
+            token_id: "jeffvader".to_string(),
+            new_token_id: "darthvader".to_string(),
+        };
+
+        // CHECK: a name with more paths than can be moved at once can't be renamed
+        // and nothing is changed
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            rename_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyPathsToMove {});
+        assert_eq!(
+            contract.num_tokens(deps.as_ref()).unwrap().count,
+            PATH_MOVE_BATCH_SIZE as u64 + 2
+        );
+        let _ = contract
+            .nft_info(deps.as_ref(), "darthvader".to_string())
+            .unwrap_err();
+
+        // CHECK: once it is down to the limit, every path comes along
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed.clone(),
+            ExecuteMsg::Burn {
+                token_id: format!("jeffvader::p{:02}", PATH_MOVE_BATCH_SIZE),
+            },
+        )
+        .unwrap();
+        entry::execute(deps.as_mut(), mock_env(), allowed, rename_msg).unwrap();
+
+        let children_res: TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Children {
+                    token_id: "darthvader".to_string(),
+                    start_after: None,
+                    limit: Some(30),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(children_res.tokens.len(), 30);
+        assert!(children_res
+            .tokens
+            .iter()
+            .all(|token_id| token_id.starts_with("darthvader::p")));
+        assert_eq!(PATH_COUNTS.load(&deps.storage, "darthvader").unwrap(), 50);
+    }
 }
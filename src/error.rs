@@ -39,4 +39,130 @@ pub enum ContractError {
 
     #[error("Format is incorrect for PGP Public Key")]
     InvalidPgpPublicKey,
+
+    #[error("Token is locked until its paths have finished burning")]
+    PathBurnPending {},
+
+    #[error("No paths are waiting to be burned for this token")]
+    NoPathBurnPending {},
+
+    #[error("Too many backup aliases")]
+    TooManyBackupAliases {},
+
+    #[error("Too many paths under this path to move in one go")]
+    TooManyPathsToMove {},
+
+    #[error("Path is nested too deeply")]
+    PathTooDeep {},
+
+    #[error("Path segment is too long")]
+    PathSegmentTooLong {},
+
+    #[error("Namespace has reached its limit of paths")]
+    NamespacePathLimitReached {},
+
+    #[error("Too many mints from this address, try again in a few blocks")]
+    AddressMintLimitReached {},
+
+    #[error("Too many mints in this block, try again in the next one")]
+    BlockMintLimitReached {},
+
+    #[error("Names in this category cannot be minted")]
+    NameCategoryDisabled {},
+
+    #[error("Prohibited by a burned fuse")]
+    FuseBurned {},
+
+    #[error("Parent must not be transferable or burnable before fuses can be burned")]
+    ParentNotFused {},
+
+    #[error("Name does not point at this contract")]
+    ContractAddressMismatch {},
+
+    #[error("Text record key can only contain lowercase letters, numbers, '.', '_' and '-'")]
+    InvalidTextRecordKey {},
+
+    #[error("Well-known text records are set with UpdateMetadata")]
+    ReservedTextRecordKey {},
+
+    #[error("Text record key is too long")]
+    TextRecordKeyTooLong {},
+
+    #[error("Text record value is too long")]
+    TextRecordValueTooLong {},
+
+    #[error("Token has reached its limit of text records")]
+    TextRecordLimitReached {},
+
+    #[error("Text record limits can't be raised above the defaults")]
+    TextRecordLimitsTooHigh {},
+
+    #[error("Bech32 prefix is not valid")]
+    InvalidBech32Prefix {},
+
+    #[error("Address is not valid for this chain")]
+    InvalidChainAddress {},
+
+    #[error("Token has reached its limit of address records")]
+    AddressRecordLimitReached {},
+
+    #[error("Contract is migrating, try again once MigrateBatch has finished")]
+    MigrationInProgress {},
+
+    #[error("No migration is in progress")]
+    NoMigrationInProgress {},
+
+    #[error("Metadata is at revision {current}, not {expected}")]
+    MetadataRevisionMismatch { expected: u64, current: u64 },
+
+    #[error("Image URL is longer than 512 bytes")]
+    ImageUrlTooLong {},
+
+    #[error("Image must be an https, ipfs or ar URL")]
+    InvalidImageUrlScheme {},
+
+    #[error("External URL is longer than 512 bytes")]
+    ExternalUrlTooLong {},
+
+    #[error("External URL must be an https, ipfs or ar URL")]
+    InvalidExternalUrlScheme {},
+
+    #[error("Email address is longer than 254 bytes")]
+    EmailTooLong {},
+
+    #[error("Public name is longer than 64 bytes")]
+    PublicNameTooLong {},
+
+    #[error("Bio is longer than 512 bytes")]
+    BioTooLong {},
+
+    #[error("Validator operator address is longer than 128 bytes")]
+    ValidatorOperatorAddressTooLong {},
+
+    #[error("Contract address is longer than 128 bytes")]
+    ContractAddressTooLong {},
+
+    #[error("PGP Public Key is longer than 8KB")]
+    PgpPublicKeyTooLong {},
+
+    #[error("Email address is not valid")]
+    InvalidEmail {},
+
+    #[error("Twitter handle is not valid")]
+    InvalidTwitterHandle {},
+
+    #[error("Discord handle is not valid")]
+    InvalidDiscordHandle {},
+
+    #[error("Telegram handle is not valid")]
+    InvalidTelegramHandle {},
+
+    #[error("Keybase handle is not valid")]
+    InvalidKeybaseHandle {},
+
+    #[error("Validator operator address is not valid bech32")]
+    InvalidValidatorOperatorAddress {},
+
+    #[error("Contract address is not valid bech32")]
+    InvalidContractAddress {},
 }
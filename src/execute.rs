@@ -1,29 +1,72 @@
 use crate::error::ContractError;
-use cosmwasm_std::{ensure_eq, Binary, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cosmwasm_std::{
+    coins, ensure_eq, Addr, BankMsg, Binary, ContractInfoResponse as WasmContractInfoResponse,
+    Deps, DepsMut, Empty, Env, MessageInfo, Order, QueryRequest, Response, StdError, StdResult,
+    Storage, Uint128, WasmQuery,
+};
 use cw2::set_contract_version;
 use cw721::Cw721ReceiveMsg;
 use cw721_base::state::TokenInfo;
+use cw_storage_plus::Bound;
 use cw_utils::{must_pay, one_coin};
 
 use std::convert::TryInto;
 
 use crate::msg::{
-    ContractInfo, InstantiateMsg, Metadata, MintMsg, MintingFeesResponse, UpdateMetadataMsg,
-    UpdateMintingFeesMsg,
+    AddressRecord, Chain, ContractInfo, Fuses, InstantiateMsg, Metadata, MintMsg,
+    MintingFeesResponse, NameCategoryPricing, PatchMetadataMsg, PathLimits, PrimaryAliasPolicy,
+    RateLimits, TextRecordLimits, UpdateMetadataMsg, UpdateMintingFeesMsg,
 };
 
-use crate::query::get_paths_for_owner_and_token;
-use crate::state::{CONTRACT_INFO, MINTING_FEES_INFO, PRIMARY_ALIASES, USERNAME_LENGTH_CAP};
+use crate::query::{get_primary_alias_and_source, walk_paths, walk_paths_leaves_first};
+use crate::state::{
+    ContractReverseRecord, MigrationStep, ADDRESS_RECORDS, BASE_TOKEN_COUNT, CONTRACT_INFO,
+    CONTRACT_REVERSE_RECORDS, DEPOSITS, FUSES, INHERIT_METADATA, METADATA_REVISIONS,
+    MIGRATION_PROGRESS, MINTING_FEES_INFO, NAMESPACE_FEES, NAME_CATEGORIES, PARENT_INDEX, PATHS,
+    PATH_COUNTS, PATH_LIMITS, PENDING_PATH_BURNS, PRIMARY_ALIASES, PRIMARY_ALIAS_POLICIES,
+    RATE_LIMITS, REGISTRATION_MODEL, TEXT_RECORDS, TEXT_RECORD_LIMITS, USERNAME_LENGTH_CAP,
+};
 use crate::utils::{
-    get_mint_fee, get_mint_response, get_number_of_owned_tokens, get_username_length, is_path,
-    path_is_valid, pgp_pubkey_format_is_valid, username_is_valid, validate_subdomain, verify_logo,
+    add_to_parent_index, apply_field_update, bump_metadata_revision, ensure_fuse_not_burned,
+    ensure_no_pending_path_burn, get_chain_key, get_fee_msgs, get_fuses, get_metadata_revision,
+    get_mint_response, get_name_category, get_name_mint_fee, get_number_of_owned_tokens,
+    get_parent_of_path, get_path_info, get_path_mint_fees, get_root_of_path, is_deposit_model,
+    is_path, move_records, namespace_in_path, parse_path, path_is_valid, path_to_token_id,
+    record_mint, remove_from_parent_index, remove_primary_alias, set_primary_alias,
+    username_is_valid, validate_address_record, validate_path_limits, validate_subdomain,
+    validate_text_record,
 };
+use crate::validation::validate_metadata;
 use crate::Cw721MetadataContract;
 
 // version info for migration info
 pub const CONTRACT_NAME: &str = "crates.io:whoami";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// how many paths can be burned in one transaction
+// anything over this is left for ContinueBurnPaths
+pub const PATH_BURN_BATCH_SIZE: usize = 50;
+
+// how many paths can be moved along with a path in one transaction
+// there is no continuation for a move, so anything bigger is rejected
+pub const PATH_MOVE_BATCH_SIZE: usize = 50;
+
+// how many tokens or aliases MigrateBatch backfills in one transaction
+pub const MIGRATION_BATCH_SIZE: usize = 50;
+
+// how many backup aliases an address can line up
+pub const MAX_BACKUP_ALIASES: usize = 10;
+
+// how many chains a token can have an address record for
+pub const MAX_ADDRESS_RECORDS: usize = 20;
+
+// text records are cleared or moved along with the token
+// on transfer, burn, rename and move, so there can't be too many
+// the admin can lower these with UpdateTextRecordLimits but not raise them
+pub const MAX_TEXT_RECORDS: u32 = 20;
+pub const MAX_TEXT_RECORD_KEY_LENGTH: u32 = 64;
+pub const MAX_TEXT_RECORD_VALUE_LENGTH: u32 = 1024;
+
 pub fn execute_instantiate(
     contract: Cw721MetadataContract,
     deps: DepsMut,
@@ -50,8 +93,14 @@ pub fn execute_instantiate(
         base_mint_fee: msg.base_mint_fee,
         burn_percentage: msg.burn_percentage,
         short_name_surcharge: msg.short_name_surcharge,
+        path_mint_fee: None,
+        bonding_curve: None,
     };
     MINTING_FEES_INFO.save(deps.storage, &minting_fees)?;
+
+    if let Some(registration_model) = msg.registration_model {
+        REGISTRATION_MODEL.save(deps.storage, &registration_model)?;
+    }
     let admin_address = deps.api.addr_validate(&msg.admin_address)?;
     contract.minter.save(deps.storage, &admin_address)?;
     Ok(Response::default())
@@ -89,6 +138,8 @@ pub fn update_minting_fees(
         base_mint_fee: msg.base_mint_fee,
         burn_percentage: msg.burn_percentage,
         short_name_surcharge: msg.short_name_surcharge,
+        path_mint_fee: msg.path_mint_fee,
+        bonding_curve: msg.bonding_curve,
     };
 
     // update
@@ -150,6 +201,230 @@ pub fn set_username_length_cap(
     Ok(res)
 }
 
+// the owner of a base token can set a fee
+// for minting paths under it
+pub fn update_namespace_fee(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    fee: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let address_trying_to_update = info.sender;
+
+    // only base tokens are namespaces
+    if is_path(&token_id) {
+        return Err(ContractError::TokenNameInvalid {});
+    }
+
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    // check it's the owner of the base token updating
+    ensure_eq!(
+        token.owner,
+        address_trying_to_update,
+        ContractError::Unauthorized {}
+    );
+
+    match fee {
+        Some(fee) if !fee.is_zero() => NAMESPACE_FEES.save(deps.storage, &token_id, &fee)?,
+        _ => NAMESPACE_FEES.remove(deps.storage, &token_id),
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_namespace_fee")
+        .add_attribute("token_id", token_id)
+        .add_attribute("fee", fee.unwrap_or_default());
+    Ok(res)
+}
+
+// the owner of a token can opt in or out
+// of inheriting metadata from its ancestors
+pub fn update_metadata_inheritance(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    inherit: bool,
+) -> Result<Response, ContractError> {
+    let address_trying_to_update = info.sender;
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    // check it's the owner of the token updating
+    ensure_eq!(
+        token.owner,
+        address_trying_to_update,
+        ContractError::Unauthorized {}
+    );
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    if inherit {
+        INHERIT_METADATA.save(deps.storage, &token_id, &Empty {})?;
+    } else {
+        INHERIT_METADATA.remove(deps.storage, &token_id);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_metadata_inheritance")
+        .add_attribute("token_id", token_id)
+        .add_attribute("inherit", inherit.to_string());
+    Ok(res)
+}
+
+// the owner of a token can irreversibly burn fuses on it
+// fuses already burned stay burned
+pub fn burn_fuses(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    fuses: Fuses,
+) -> Result<Response, ContractError> {
+    let address_trying_to_burn = info.sender;
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    // check it's the owner of the token burning fuses
+    ensure_eq!(
+        token.owner,
+        address_trying_to_burn,
+        ContractError::Unauthorized {}
+    );
+
+    // a path can be burned or transferred away along with its parent
+    // so its fuses only mean something once its parent's are burned
+    if is_path(&token_id) {
+        let parent_fuses = match token.extension.parent_token_id {
+            Some(ref parent) => get_fuses(deps.storage, parent)?,
+            None => Fuses::default(),
+        };
+        if !(parent_fuses.cannot_transfer && parent_fuses.cannot_burn) {
+            return Err(ContractError::ParentNotFused {});
+        }
+    }
+
+    let existing = get_fuses(deps.storage, &token_id)?;
+    let burned = Fuses {
+        cannot_transfer: existing.cannot_transfer || fuses.cannot_transfer,
+        cannot_update_metadata: existing.cannot_update_metadata || fuses.cannot_update_metadata,
+        cannot_mint_paths: existing.cannot_mint_paths || fuses.cannot_mint_paths,
+        cannot_burn: existing.cannot_burn || fuses.cannot_burn,
+    };
+    FUSES.save(deps.storage, &token_id, &burned)?;
+
+    let res = Response::new()
+        .add_attribute("action", "burn_fuses")
+        .add_attribute("owner", address_trying_to_burn)
+        .add_attribute("token_id", token_id);
+    Ok(res)
+}
+
+// the admin addr can update the limits on paths
+pub fn update_path_limits(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: PathLimits,
+) -> Result<Response, ContractError> {
+    let address_trying_to_update = info.sender;
+    let current_admin_address = contract.minter(deps.as_ref())?.minter;
+
+    // check it's the admin of the contract updating
+    ensure_eq!(
+        current_admin_address,
+        address_trying_to_update,
+        ContractError::Unauthorized {}
+    );
+
+    // update
+    PATH_LIMITS.save(deps.storage, &msg)?;
+
+    let res = Response::new().add_attribute("action", "update_path_limits");
+    Ok(res)
+}
+
+// the admin addr can update the limits on text records
+pub fn update_text_record_limits(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: TextRecordLimits,
+) -> Result<Response, ContractError> {
+    let current_admin_address = contract.minter(deps.as_ref())?.minter;
+
+    // check it's the admin of the contract updating
+    ensure_eq!(
+        current_admin_address,
+        info.sender,
+        ContractError::Unauthorized {}
+    );
+
+    let within_caps = msg.max_key_length.unwrap_or_default() <= MAX_TEXT_RECORD_KEY_LENGTH
+        && msg.max_value_length.unwrap_or_default() <= MAX_TEXT_RECORD_VALUE_LENGTH
+        && msg.max_records.unwrap_or_default() <= MAX_TEXT_RECORDS;
+    if !within_caps {
+        return Err(ContractError::TextRecordLimitsTooHigh {});
+    }
+
+    TEXT_RECORD_LIMITS.save(deps.storage, &msg)?;
+
+    Ok(Response::new().add_attribute("action", "update_text_record_limits"))
+}
+
+// the admin addr can update the limits on how quickly tokens are minted
+pub fn update_rate_limits(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: RateLimits,
+) -> Result<Response, ContractError> {
+    let address_trying_to_update = info.sender;
+    let current_admin_address = contract.minter(deps.as_ref())?.minter;
+
+    // check it's the admin of the contract updating
+    ensure_eq!(
+        current_admin_address,
+        address_trying_to_update,
+        ContractError::Unauthorized {}
+    );
+
+    // update
+    RATE_LIMITS.save(deps.storage, &msg)?;
+
+    let res = Response::new().add_attribute("action", "update_rate_limits");
+    Ok(res)
+}
+
+// the admin addr can update which name categories are priced separately
+pub fn update_name_categories(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    categories: Vec<NameCategoryPricing>,
+) -> Result<Response, ContractError> {
+    let address_trying_to_update = info.sender;
+    let current_admin_address = contract.minter(deps.as_ref())?.minter;
+
+    // check it's the admin of the contract updating
+    ensure_eq!(
+        current_admin_address,
+        address_trying_to_update,
+        ContractError::Unauthorized {}
+    );
+
+    // update
+    NAME_CATEGORIES.save(deps.storage, &categories)?;
+
+    let res = Response::new().add_attribute("action", "update_name_categories");
+    Ok(res)
+}
+
 // this actually updates the ADMIN address, but under the hood it is
 // called minter by the contract.
 // On the query side we actually just proxy to the existing Minter query
@@ -186,7 +461,7 @@ pub fn set_admin_address(
 pub fn mint(
     contract: Cw721MetadataContract,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: MintMsg,
 ) -> Result<Response, ContractError> {
@@ -201,17 +476,11 @@ pub fn mint(
         ContractError::Unauthorized {}
     );
 
-    // validate any embedded logo
-    if let Some(ref pfp_data) = msg.extension.image_data {
-        verify_logo(pfp_data)?
-    }
+    // check this is within the rate limits set by the admin
+    record_mint(deps.storage, env.block.height, &address_trying_to_mint)?;
 
-    // validate PGP pubkey format
-    if let Some(ref pgp_public_key) = msg.extension.pgp_public_key {
-        if !pgp_pubkey_format_is_valid(pgp_public_key) {
-            return Err(ContractError::InvalidPgpPublicKey {});
-        }
-    }
+    // validate the metadata, including any embedded logo
+    validate_metadata(&msg.extension)?;
 
     // get minting fees and minter (i.e. admin)
     let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
@@ -253,6 +522,9 @@ pub fn mint(
         return Err(ContractError::TokenNameInvalid {});
     }
 
+    // can't reclaim a name until its old paths are gone
+    ensure_no_pending_path_burn(deps.as_ref(), username)?;
+
     // if parent_token_id is set,
     // this is a subdomain
     // we also check for cycles
@@ -260,6 +532,7 @@ pub fn mint(
         if parent_token_id == username || is_path(parent_token_id) {
             return Err(ContractError::CycleDetected {});
         } else {
+            ensure_no_pending_path_burn(deps.as_ref(), parent_token_id)?;
             validate_subdomain(
                 &contract,
                 &deps,
@@ -269,8 +542,16 @@ pub fn mint(
         }
     }
 
+    // some categories of name are priced separately
+    // or can't be minted at all
+    let category = get_name_category(deps.storage, username)?;
+    if matches!(category, Some(ref pricing) if !pricing.enabled) {
+        return Err(ContractError::NameCategoryDisabled {});
+    }
+
     // work out what fees are owed
-    let fee = get_mint_fee(minting_fees.clone(), get_username_length(username));
+    let base_token_count = BASE_TOKEN_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let fee = get_name_mint_fee(minting_fees.clone(), username, &category, base_token_count);
     // error out if this fee isn't covered in the msg
     if fee.is_some() {
         must_pay(&info, &minting_fees.native_denom)?;
@@ -287,6 +568,7 @@ pub fn mint(
     // create the token
     // this will fail if token_id (i.e. username)
     // is already claimed
+    let parent_token_id = msg.extension.parent_token_id.clone();
     let token = TokenInfo {
         owner: owner_address,
         approvals: vec![],
@@ -300,7 +582,22 @@ pub fn mint(
             None => Ok(token),
         })?;
 
+    // index under the parent, if this is a subdomain
+    add_to_parent_index(deps.storage, username, &parent_token_id)?;
+
     contract.increment_tokens(deps.storage)?;
+    BASE_TOKEN_COUNT.save(deps.storage, &(base_token_count + 1))?;
+
+    // in the deposit model the fee is held by the contract
+    // rather than being paid out
+    let fee = if is_deposit_model(deps.storage)? {
+        if let Some(fee_amount) = fee {
+            DEPOSITS.save(deps.storage, username, &fee_amount)?;
+        }
+        None
+    } else {
+        fee
+    };
 
     // if there is a fee, add a bank msg to send to the admin_address
     let res = get_mint_response(
@@ -318,17 +615,18 @@ pub fn mint(
 // essentially what we call a reified subdomain/namespace
 // where the whole slug is a single item
 // paths are different from names
-// they are free to mint, and have no cap
+// they are free to mint, and are only capped
+// by any path limits the admin has set
 pub fn mint_path(
     contract: Cw721MetadataContract,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: MintMsg,
 ) -> Result<Response, ContractError> {
     // any address can mint
     // sender of the execute
-    let address_trying_to_mint = info.sender;
+    let address_trying_to_mint = info.sender.clone();
 
     // can only mint NFTs belonging to yourself
     ensure_eq!(
@@ -337,18 +635,11 @@ pub fn mint_path(
         ContractError::Unauthorized {}
     );
 
-    // validate any embedded logo or image
-    if let Some(ref pfp_data) = msg.extension.image_data {
-        verify_logo(pfp_data)?
-    }
+    // check this is within the rate limits set by the admin
+    record_mint(deps.storage, env.block.height, &address_trying_to_mint)?;
 
-    // validate PGP public key format
-    // or return an error
-    if let Some(ref pgp_public_key) = msg.extension.pgp_public_key {
-        if !pgp_pubkey_format_is_valid(pgp_public_key) {
-            return Err(ContractError::InvalidPgpPublicKey {});
-        }
-    }
+    // validate the metadata, including any embedded logo
+    validate_metadata(&msg.extension)?;
 
     // validate owner addr
     let owner_address = deps.api.addr_validate(&msg.owner)?;
@@ -360,16 +651,22 @@ pub fn mint_path(
     // if parent_token_id is set,
     // this is a path (if not, it's invalid)
     // we also check for cycles
-    // we also check that parent token isn't repeated in the path
     if let Some(ref parent_token_id) = msg.extension.parent_token_id {
         if parent_token_id == path {
             Err(ContractError::CycleDetected {})
         } else {
             // first we validate path
-            if !path_is_valid(path, parent_token_id) {
+            if !path_is_valid(path) {
                 return Err(ContractError::TokenNameInvalid {});
             }
 
+            // nothing can be added under a name
+            // that is still having its paths burned
+            ensure_no_pending_path_burn(deps.as_ref(), parent_token_id)?;
+
+            // or under a parent that has burned the fuse for it
+            ensure_fuse_not_burned(deps.as_ref(), parent_token_id, |f| f.cannot_mint_paths)?;
+
             // then its hierarchy
             validate_subdomain(
                 &contract,
@@ -378,11 +675,54 @@ pub fn mint_path(
                 address_trying_to_mint.clone(),
             )?;
 
-            // okay, it's valid, prepend it with parent and start the show
-            let full_path = format!("{}::{}", parent_token_id, path);
+            // okay, it's valid, add it as a segment under the parent
+            // and start the show
+            let mut path_info = get_path_info(deps.storage, parent_token_id)?;
+            path_info.segments.push(path.to_string());
+            let full_path = path_to_token_id(&path_info);
+
+            // check it is within the limits set by the admin
+            validate_path_limits(
+                deps.storage,
+                &path_info.root,
+                path_info.segments.len(),
+                path,
+                1,
+            )?;
+
+            // work out what fees are owed
+            // the owner of the base token doesn't pay themselves
+            let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+            let (protocol_fee, namespace_fee) =
+                get_path_mint_fees(deps.storage, &minting_fees, parent_token_id)?;
+            let namespace_owner = match namespace_fee {
+                Some(_) => {
+                    let owner = contract.tokens.load(deps.storage, &path_info.root)?.owner;
+                    if owner == address_trying_to_mint {
+                        None
+                    } else {
+                        Some(owner)
+                    }
+                }
+                None => None,
+            };
+            let namespace_fee = namespace_owner.as_ref().and(namespace_fee);
+
+            // error out if this fee isn't covered in the msg
+            let total_fee = protocol_fee.unwrap_or_default() + namespace_fee.unwrap_or_default();
+            if !total_fee.is_zero() {
+                must_pay(&info, &minting_fees.native_denom)?;
+
+                // ensure atomicity
+                let coin = one_coin(&info)?;
+                if coin.amount < total_fee {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+            }
 
             // create the token
             // this will fail if claimed
+            let parent_token_id = parent_token_id.clone();
             let token = TokenInfo {
                 owner: owner_address,
                 approvals: vec![],
@@ -396,13 +736,31 @@ pub fn mint_path(
                     None => Ok(token),
                 })?;
 
+            // store its structure and index under the parent
+            PATHS.save(deps.storage, &full_path, &path_info)?;
+            add_to_parent_index(deps.storage, &full_path, &Some(parent_token_id))?;
+
             contract.increment_tokens(deps.storage)?;
 
-            let res = Response::new()
-                .add_attribute("action", "mint")
-                .add_attribute("minter", address_trying_to_mint)
-                .add_attribute("token_id", full_path);
-            Ok(res)
+            // the protocol fee is split like any other mint fee
+            // and the namespace fee goes to the owner of the base token
+            let minter = contract.minter(deps.as_ref())?.minter;
+            let admin_address = deps.api.addr_validate(&minter)?;
+            let res = get_mint_response(
+                admin_address,
+                address_trying_to_mint,
+                minting_fees.native_denom.clone(),
+                protocol_fee,
+                minting_fees.burn_percentage,
+                full_path,
+            );
+            match (namespace_fee, namespace_owner) {
+                (Some(fee), Some(owner)) => Ok(res.add_message(BankMsg::Send {
+                    to_address: owner.to_string(),
+                    amount: coins(fee.u128(), minting_fees.native_denom),
+                })),
+                _ => Ok(res),
+            }
         }
     } else {
         Err(ContractError::ParentNotFound {})
@@ -436,10 +794,10 @@ pub fn update_metadata(
         ContractError::Unauthorized {}
     );
 
-    // validate any embedded logo
-    if let Some(ref pfp_data) = msg.metadata.image_data {
-        verify_logo(pfp_data)?
-    }
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    // validate the metadata, including any embedded logo
+    validate_metadata(&msg.metadata)?;
 
     // arrrrre you ready to rrrrrumb-
     // rrredefine some metadata?
@@ -455,11 +813,479 @@ pub fn update_metadata(
                 None => Ok(username_nft),
             }
         })?;
+    let revision = bump_metadata_revision(deps.storage, &token_id)?;
 
     Ok(Response::new()
         .add_attribute("action", "update_metadata")
         .add_attribute("owner", info.sender)
-        .add_attribute("token_id", token_id))
+        .add_attribute("token_id", token_id)
+        .add_attribute("revision", revision.to_string()))
+}
+
+// like update_metadata, but only touches the fields given
+pub fn patch_metadata(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: PatchMetadataMsg,
+) -> Result<Response, ContractError> {
+    let token_id = msg.token_id;
+    let mut token = contract.tokens.load(deps.storage, &token_id)?;
+
+    // check it's the owner of the NFT updating meta
+    ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    // reject the patch if someone else got there first
+    let current = get_metadata_revision(deps.storage, &token_id)?;
+    if let Some(expected) = msg.expected_revision {
+        if expected != current {
+            return Err(ContractError::MetadataRevisionMismatch { expected, current });
+        }
+    }
+
+    let metadata = &mut token.extension;
+    apply_field_update(&mut metadata.image, msg.image);
+    apply_field_update(&mut metadata.image_data, msg.image_data);
+    apply_field_update(&mut metadata.email, msg.email);
+    apply_field_update(&mut metadata.external_url, msg.external_url);
+    apply_field_update(&mut metadata.public_name, msg.public_name);
+    apply_field_update(&mut metadata.public_bio, msg.public_bio);
+    apply_field_update(&mut metadata.twitter_id, msg.twitter_id);
+    apply_field_update(&mut metadata.discord_id, msg.discord_id);
+    apply_field_update(&mut metadata.telegram_id, msg.telegram_id);
+    apply_field_update(&mut metadata.keybase_id, msg.keybase_id);
+    apply_field_update(
+        &mut metadata.validator_operator_address,
+        msg.validator_operator_address,
+    );
+    apply_field_update(&mut metadata.contract_address, msg.contract_address);
+    apply_field_update(&mut metadata.pgp_public_key, msg.pgp_public_key);
+
+    // the patched metadata is held to the same rules as a full update
+    validate_metadata(metadata)?;
+
+    contract.tokens.save(deps.storage, &token_id, &token)?;
+    let revision = bump_metadata_revision(deps.storage, &token_id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "patch_metadata")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("revision", revision.to_string()))
+}
+
+// moves a path under a new parent and/or gives it a new name
+// the path and everything under it are re-keyed
+// metadata and approvals come along with them
+pub fn move_path(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    new_parent: Option<String>,
+    new_name: Option<String>,
+) -> Result<Response, ContractError> {
+    let address_trying_to_move = info.sender;
+
+    // only paths can be moved
+    // base tokens are their own root
+    let path_info = match PATHS.may_load(deps.storage, &token_id)? {
+        Some(path_info) => path_info,
+        None => return Err(ContractError::TokenNameInvalid {}),
+    };
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    let old_parent = match token.extension.parent_token_id {
+        Some(ref parent) => parent.clone(),
+        None => return Err(ContractError::ParentNotFound {}),
+    };
+
+    // check it's the owner of the path moving it
+    ensure_eq!(
+        token.owner,
+        address_trying_to_move,
+        ContractError::Unauthorized {}
+    );
+
+    let new_parent = new_parent.unwrap_or(old_parent.clone());
+    let new_name = match new_name {
+        Some(name) => name.to_lowercase(),
+        None => path_info.segments.last().cloned().unwrap_or_default(),
+    };
+    if !path_is_valid(&new_name) {
+        return Err(ContractError::TokenNameInvalid {});
+    }
+
+    // can't move a path under itself
+    if new_parent == token_id || namespace_in_path(&new_parent, &token_id) {
+        return Err(ContractError::CycleDetected {});
+    }
+
+    // a path with fuses burned stays where it is
+    // otherwise it could be moved out from under its parent's fuses
+    if get_fuses(deps.storage, &token_id)? != Fuses::default() {
+        return Err(ContractError::FuseBurned {});
+    }
+
+    // nothing can be moved under a parent
+    // that has burned the fuse for minting paths
+    if new_parent != old_parent {
+        ensure_fuse_not_burned(deps.as_ref(), &new_parent, |f| f.cannot_mint_paths)?;
+    }
+
+    // nothing moves in or out of a name
+    // that is still having its paths burned
+    ensure_no_pending_path_burn(deps.as_ref(), &token_id)?;
+    ensure_no_pending_path_burn(deps.as_ref(), &new_parent)?;
+
+    // the sender must own the current parent
+    // so nothing can be taken out of someone else's namespace
+    validate_subdomain(
+        &contract,
+        &deps,
+        old_parent.clone(),
+        address_trying_to_move.clone(),
+    )?;
+
+    // and the new parent too
+    validate_subdomain(
+        &contract,
+        &deps,
+        new_parent.clone(),
+        address_trying_to_move.clone(),
+    )?;
+
+    let mut new_path_info = get_path_info(deps.storage, &new_parent)?;
+    new_path_info.segments.push(new_name);
+    let new_token_id = path_to_token_id(&new_path_info);
+
+    // everything under the path moves too
+    // but only as much as we can safely do in one go
+    let descendants = walk_paths(
+        deps.as_ref(),
+        &token_id,
+        None,
+        PATH_MOVE_BATCH_SIZE + 1,
+        |_| Ok(true),
+    )?;
+    if descendants.len() > PATH_MOVE_BATCH_SIZE {
+        return Err(ContractError::TooManyPathsToMove {});
+    }
+
+    // check the moved paths are within the limits set by the admin
+    // the deepest of them sets the depth
+    let deepest = descendants
+        .iter()
+        .map(|id| parse_path(id).segments.len() - path_info.segments.len())
+        .max()
+        .unwrap_or_default();
+    let new_paths = if new_path_info.root == path_info.root {
+        0
+    } else {
+        descendants.len() as u32 + 1
+    };
+    validate_path_limits(
+        deps.storage,
+        &new_path_info.root,
+        new_path_info.segments.len() + deepest,
+        new_path_info.segments.last().unwrap(),
+        new_paths,
+    )?;
+
+    // parents come before children
+    // so each parent has been re-keyed by the time we get to its children
+    let mut moves = vec![(token_id.clone(), new_token_id.clone())];
+    for old_id in descendants {
+        let new_id = format!("{}{}", new_token_id, &old_id[token_id.len()..]);
+        moves.push((old_id, new_id));
+    }
+
+    for (old_id, new_id) in &moves {
+        let new_parent_id = if old_id == &token_id {
+            new_parent.clone()
+        } else {
+            let parent = get_parent_of_path(old_id).unwrap_or_default();
+            format!("{}{}", new_token_id, &parent[token_id.len()..])
+        };
+        rekey_token(&contract, deps.storage, old_id, new_id, Some(new_parent_id))?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "move_path")
+        .add_attribute("owner", address_trying_to_move)
+        .add_attribute("token_id", token_id)
+        .add_attribute("new_token_id", new_token_id))
+}
+
+// renames a base token
+// the token and every path under it are re-keyed to the new name
+// metadata, approvals and settings come along with them
+// the difference in minting fee between the names is charged
+pub fn rename(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    new_token_id: String,
+) -> Result<Response, ContractError> {
+    let address_trying_to_rename = info.sender.clone();
+
+    // paths are renamed with MovePath
+    if is_path(&token_id) {
+        return Err(ContractError::TokenNameInvalid {});
+    }
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    // check it's the owner of the token renaming it
+    ensure_eq!(
+        token.owner,
+        address_trying_to_rename,
+        ContractError::Unauthorized {}
+    );
+
+    // username == token_id
+    // normalize it to lowercase
+    let new_token_id = new_token_id.to_lowercase();
+    if !username_is_valid(deps.as_ref(), &new_token_id) {
+        return Err(ContractError::TokenNameInvalid {});
+    }
+    if new_token_id == token_id {
+        return Err(ContractError::Claimed {});
+    }
+
+    // a fused name keeps its name
+    if get_fuses(deps.storage, &token_id)? != Fuses::default() {
+        return Err(ContractError::FuseBurned {});
+    }
+
+    // nothing is renamed while paths are being burned
+    // under either name
+    ensure_no_pending_path_burn(deps.as_ref(), &token_id)?;
+    ensure_no_pending_path_burn(deps.as_ref(), &new_token_id)?;
+
+    // every path under the name moves too
+    // as do any base tokens nested directly under it
+    // but only as much as we can safely do in one go
+    let descendants = walk_paths(
+        deps.as_ref(),
+        &token_id,
+        None,
+        PATH_MOVE_BATCH_SIZE + 1,
+        |_| Ok(true),
+    )?;
+    let nested_base_tokens = PARENT_INDEX
+        .prefix(&token_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|child| !matches!(child, Ok(id) if is_path(id)))
+        .take(PATH_MOVE_BATCH_SIZE + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    if descendants.len() + nested_base_tokens.len() > PATH_MOVE_BATCH_SIZE {
+        return Err(ContractError::TooManyPathsToMove {});
+    }
+
+    // the new name must be one that could be minted
+    let new_category = get_name_category(deps.storage, &new_token_id)?;
+    if matches!(new_category, Some(ref pricing) if !pricing.enabled) {
+        return Err(ContractError::NameCategoryDisabled {});
+    }
+
+    // work out what fees are owed
+    // only the difference is charged, nothing is refunded
+    let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+    let old_category = get_name_category(deps.storage, &token_id)?;
+    let base_token_count = BASE_TOKEN_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let old_fee = get_name_mint_fee(
+        minting_fees.clone(),
+        &token_id,
+        &old_category,
+        base_token_count,
+    );
+    let new_fee = get_name_mint_fee(
+        minting_fees.clone(),
+        &new_token_id,
+        &new_category,
+        base_token_count,
+    );
+    let fee = new_fee
+        .unwrap_or_default()
+        .saturating_sub(old_fee.unwrap_or_default());
+
+    // error out if this fee isn't covered in the msg
+    if !fee.is_zero() {
+        must_pay(&info, &minting_fees.native_denom)?;
+
+        // ensure atomicity
+        let coin = one_coin(&info)?;
+        if coin.amount < fee {
+            return Err(ContractError::InsufficientFunds {});
+        }
+    }
+
+    // re-key the name itself
+    // this will fail if the new name is claimed
+    rekey_token(
+        &contract,
+        deps.storage,
+        &token_id,
+        &new_token_id,
+        token.extension.parent_token_id,
+    )?;
+    if let Some(namespace_fee) = NAMESPACE_FEES.may_load(deps.storage, &token_id)? {
+        NAMESPACE_FEES.remove(deps.storage, &token_id);
+        NAMESPACE_FEES.save(deps.storage, &new_token_id, &namespace_fee)?;
+    }
+
+    // any deposit moves with the name
+    // and in the deposit model, so does the fee
+    let deposit_model = is_deposit_model(deps.storage)?;
+    let mut deposit = DEPOSITS
+        .may_load(deps.storage, &token_id)?
+        .unwrap_or_default();
+    if deposit_model {
+        deposit += fee;
+    }
+    DEPOSITS.remove(deps.storage, &token_id);
+    if !deposit.is_zero() {
+        DEPOSITS.save(deps.storage, &new_token_id, &deposit)?;
+    }
+
+    // parents come before children
+    // so the new ids line up with the new parent ids
+    for old_id in descendants {
+        let new_id = format!("{}{}", new_token_id, &old_id[token_id.len()..]);
+        let new_parent_id = get_parent_of_path(&new_id).map(|parent| parent.to_string());
+        rekey_token(&contract, deps.storage, &old_id, &new_id, new_parent_id)?;
+    }
+
+    // nested base tokens keep their name, but point at the new one
+    for child_id in nested_base_tokens {
+        let old_parent_id = Some(token_id.clone());
+        let new_parent_id = Some(new_token_id.clone());
+        remove_from_parent_index(deps.storage, &child_id, &old_parent_id)?;
+        contract
+            .tokens
+            .update(deps.storage, &child_id, |child| -> StdResult<_> {
+                let mut child = child.ok_or_else(|| StdError::not_found("token"))?;
+                child.extension.parent_token_id = new_parent_id.clone();
+                Ok(child)
+            })?;
+        add_to_parent_index(deps.storage, &child_id, &new_parent_id)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "rename")
+        .add_attribute("owner", address_trying_to_rename)
+        .add_attribute("token_id", token_id)
+        .add_attribute("new_token_id", new_token_id);
+    if !fee.is_zero() && !deposit_model {
+        let minter = contract.minter(deps.as_ref())?.minter;
+        let admin_address = deps.api.addr_validate(&minter)?;
+        res = res.add_messages(get_fee_msgs(
+            admin_address,
+            minting_fees.native_denom,
+            fee,
+            minting_fees.burn_percentage,
+        ));
+    }
+    Ok(res)
+}
+
+// moves a token to a new id, with a new parent
+// along with its metadata, approvals, path structure
+// inheritance and the owner's primary alias
+// this will fail if the new id is claimed
+fn rekey_token(
+    contract: &Cw721MetadataContract,
+    storage: &mut dyn Storage,
+    old_id: &str,
+    new_id: &str,
+    new_parent_id: Option<String>,
+) -> Result<(), ContractError> {
+    let mut token = contract.tokens.load(storage, old_id)?;
+
+    remove_from_parent_index(storage, old_id, &token.extension.parent_token_id)?;
+    PATHS.remove(storage, old_id);
+    let inherit = INHERIT_METADATA.has(storage, old_id);
+    INHERIT_METADATA.remove(storage, old_id);
+    contract.tokens.remove(storage, old_id)?;
+
+    token.extension.parent_token_id = new_parent_id.clone();
+    let owner = token.owner.clone();
+    let metadata = token.extension.clone();
+    contract.tokens.update(storage, new_id, |old| match old {
+        Some(_) => Err(ContractError::Claimed {}),
+        None => Ok(token),
+    })?;
+    if is_path(new_id) {
+        PATHS.save(storage, new_id, &parse_path(new_id))?;
+    }
+    if inherit {
+        INHERIT_METADATA.save(storage, new_id, &Empty {})?;
+    }
+    move_records(storage, old_id, Some(new_id))?;
+    if let Some(revision) = METADATA_REVISIONS.may_load(storage, old_id)? {
+        METADATA_REVISIONS.remove(storage, old_id);
+        METADATA_REVISIONS.save(storage, new_id, &revision)?;
+    }
+    // a burn that is still under way carries on under the new id
+    // so whatever is left below stays locked until it is finished
+    if let Some(pending_owner) = PENDING_PATH_BURNS.may_load(storage, old_id)? {
+        PENDING_PATH_BURNS.remove(storage, old_id);
+        PENDING_PATH_BURNS.save(storage, new_id, &pending_owner)?;
+    }
+    add_to_parent_index(storage, new_id, &new_parent_id)?;
+    rekey_contract_reverse_record(storage, old_id, Some(new_id), &metadata)?;
+
+    // point the owner's primary alias at the new id
+    if PRIMARY_ALIASES.may_load(storage, &owner)?.as_deref() == Some(old_id) {
+        set_primary_alias(storage, &owner, new_id)?;
+    }
+    // and any backup alias
+    if let Some(PrimaryAliasPolicy::Backups { mut aliases }) =
+        PRIMARY_ALIAS_POLICIES.may_load(storage, &owner)?
+    {
+        if let Some(alias) = aliases.iter_mut().find(|alias| *alias == old_id) {
+            *alias = new_id.to_string();
+            PRIMARY_ALIAS_POLICIES.save(
+                storage,
+                &owner,
+                &PrimaryAliasPolicy::Backups { aliases },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// a contract's reverse record follows its name when it is renamed or moved
+// and goes when it is burned or changes hands
+// the record can only have been set while the name pointed at the contract
+// so that is where to look for it
+fn rekey_contract_reverse_record(
+    storage: &mut dyn Storage,
+    old_id: &str,
+    new_id: Option<&str>,
+    metadata: &Metadata,
+) -> Result<(), ContractError> {
+    let contract_addr = match metadata.contract_address {
+        Some(ref contract_address) => Addr::unchecked(contract_address),
+        None => return Ok(()),
+    };
+    if let Some(mut record) = CONTRACT_REVERSE_RECORDS.may_load(storage, &contract_addr)? {
+        if record.token_id == old_id {
+            match new_id {
+                Some(new_id) => {
+                    record.token_id = new_id.to_string();
+                    CONTRACT_REVERSE_RECORDS.save(storage, &contract_addr, &record)?;
+                }
+                None => CONTRACT_REVERSE_RECORDS.remove(storage, &contract_addr),
+            }
+        }
+    }
+    Ok(())
 }
 
 // look up token_id
@@ -485,7 +1311,7 @@ pub fn update_primary_alias(
     );
 
     // always overwrite
-    PRIMARY_ALIASES.save(deps.storage, &address_trying_to_update, &token_id)?;
+    set_primary_alias(deps.storage, &address_trying_to_update, &token_id)?;
 
     let res = Response::new()
         .add_attribute("action", "update_preferred_alias")
@@ -494,22 +1320,251 @@ pub fn update_primary_alias(
     Ok(res)
 }
 
+// sets what happens to the sender's primary alias
+// when it leaves their account
+pub fn update_primary_alias_policy(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    policy: PrimaryAliasPolicy,
+) -> Result<Response, ContractError> {
+    if let PrimaryAliasPolicy::Backups { aliases } = &policy {
+        if aliases.len() > MAX_BACKUP_ALIASES {
+            return Err(ContractError::TooManyBackupAliases {});
+        }
+
+        // backups must belong to the sender when they are set
+        // they are checked again when one is needed
+        for alias in aliases {
+            let token = contract.tokens.load(deps.storage, alias)?;
+            ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+        }
+    }
+
+    PRIMARY_ALIAS_POLICIES.save(deps.storage, &info.sender, &policy)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_primary_alias_policy")
+        .add_attribute("address", info.sender))
+}
+
+// contracts can't easily set their own primary alias
+// so their wasm admin can do it for them
+pub fn set_contract_reverse_record(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    target_contract: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let target_contract_addr = deps.api.addr_validate(&target_contract)?;
+
+    let wasm_contract_info: WasmContractInfoResponse =
+        deps.querier
+            .query(&QueryRequest::Wasm(WasmQuery::ContractInfo {
+                contract_addr: target_contract_addr.to_string(),
+            }))?;
+    if wasm_contract_info.admin.as_deref() != Some(info.sender.as_str()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    if token.extension.contract_address.as_deref() != Some(target_contract_addr.as_str()) {
+        return Err(ContractError::ContractAddressMismatch {});
+    }
+
+    CONTRACT_REVERSE_RECORDS.save(
+        deps.storage,
+        &target_contract_addr,
+        &ContractReverseRecord {
+            token_id: token_id.clone(),
+            owner: token.owner,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_contract_reverse_record")
+        .add_attribute("sender", info.sender)
+        .add_attribute("contract", target_contract_addr)
+        .add_attribute("username", token_id))
+}
+
+pub fn set_text_record(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    key: String,
+    value: String,
+) -> Result<Response, ContractError> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    let new_record = !TEXT_RECORDS.has(deps.storage, (&token_id, &key));
+    validate_text_record(deps.storage, &token_id, &key, &value, new_record)?;
+
+    TEXT_RECORDS.save(deps.storage, (&token_id, &key), &value)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_text_record")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("key", key))
+}
+
+pub fn remove_text_record(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    key: String,
+) -> Result<Response, ContractError> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    TEXT_RECORDS.remove(deps.storage, (&token_id, &key));
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_text_record")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("key", key))
+}
+
+pub fn set_address_record(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    chain: Chain,
+    address: String,
+) -> Result<Response, ContractError> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    validate_address_record(&chain, &address)?;
+
+    let chain_key = get_chain_key(&chain);
+    if !ADDRESS_RECORDS.has(deps.storage, (&token_id, &chain_key)) {
+        let count = ADDRESS_RECORDS
+            .prefix(&token_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(MAX_ADDRESS_RECORDS)
+            .count();
+        if count >= MAX_ADDRESS_RECORDS {
+            return Err(ContractError::AddressRecordLimitReached {});
+        }
+    }
+
+    ADDRESS_RECORDS.save(
+        deps.storage,
+        (&token_id, &chain_key),
+        &AddressRecord {
+            chain,
+            address: address.clone(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_address_record")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("chain", chain_key)
+        .add_attribute("address", address))
+}
+
+pub fn remove_address_record(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    chain: Chain,
+) -> Result<Response, ContractError> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    ensure_eq!(token.owner, info.sender, ContractError::Unauthorized {});
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_update_metadata)?;
+
+    let chain_key = get_chain_key(&chain);
+    ADDRESS_RECORDS.remove(deps.storage, (&token_id, &chain_key));
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_address_record")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("chain", chain_key))
+}
+
 //
 // --- we override these purely so we can clear any preferred aliases and sub paths on transfer or burn
 //
 
+// if token_id is its owner's primary alias, replace it according to
+// the owner's policy, with the first backup they still own, or clear it
 pub fn clear_alias_if_primary(deps: DepsMut, token_id: String) -> Result<(), ContractError> {
     let contract = Cw721MetadataContract::default();
     let username_nft = contract.tokens.load(deps.storage, &token_id)?;
-    let primary_alias = PRIMARY_ALIASES.may_load(deps.storage, &username_nft.owner)?;
-    if let Some(alias) = primary_alias {
-        if alias == token_id {
-            PRIMARY_ALIASES.remove(deps.storage, &username_nft.owner);
+    let owner = username_nft.owner;
+    if PRIMARY_ALIASES.may_load(deps.storage, &owner)?.as_deref() != Some(token_id.as_str()) {
+        return Ok(());
+    }
+
+    let mut replacement = None;
+    if let Some(PrimaryAliasPolicy::Backups { aliases }) =
+        PRIMARY_ALIAS_POLICIES.may_load(deps.storage, &owner)?
+    {
+        for alias in aliases {
+            if alias == token_id {
+                continue;
+            }
+            if let Some(token) = contract.tokens.may_load(deps.storage, &alias)? {
+                if token.owner == owner {
+                    replacement = Some(alias);
+                    break;
+                }
+            }
         }
     }
+
+    match replacement {
+        Some(alias) => set_primary_alias(deps.storage, &owner, &alias)?,
+        None => remove_primary_alias(deps.storage, &owner)?,
+    }
     Ok(())
 }
 
+// whether a base name arriving in an account should become its primary alias
+// only if nothing resolves for the account yet, however it would resolve
+// and the account hasn't opted out of having an alias picked for it
+// this has to be asked before the transfer, or the name itself would resolve
+// like the first token fallback, paths are left out
+fn should_set_alias_on_receive(
+    contract: &Cw721MetadataContract,
+    deps: Deps,
+    recipient: &str,
+    token_id: &str,
+) -> Result<bool, ContractError> {
+    if is_path(token_id) {
+        return Ok(false);
+    }
+    let recipient_addr = deps.api.addr_validate(recipient)?;
+    let policy = PRIMARY_ALIAS_POLICIES
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    if policy == PrimaryAliasPolicy::None {
+        return Ok(false);
+    }
+    Ok(get_primary_alias_and_source(contract, deps, &recipient_addr)?.is_none())
+}
+
 // this function clears metadata
 // for situations like transfer and send
 // to enable web of trust stuff
@@ -517,12 +1572,33 @@ pub fn clear_alias_if_primary(deps: DepsMut, token_id: String) -> Result<(), Con
 pub fn clear_metadata(deps: DepsMut, token_id: String) -> Result<(), ContractError> {
     let contract = Cw721MetadataContract::default();
     let username_nft = contract.tokens.load(deps.storage, &token_id)?;
+
+    // any namespace fee, inheritance, text or address record is cleared along with everything else
+    NAMESPACE_FEES.remove(deps.storage, &token_id);
+    INHERIT_METADATA.remove(deps.storage, &token_id);
+    move_records(deps.storage, &token_id, None)?;
+
+    // a path stays where it is in its namespace
+    // so that it is burned along with the rest of it
+    // for anything else, the parent is cleared along with everything else
+    let parent_token_id = if is_path(&token_id) {
+        username_nft.extension.parent_token_id.clone()
+    } else {
+        remove_from_parent_index(
+            deps.storage,
+            &token_id,
+            &username_nft.extension.parent_token_id,
+        )?;
+        None
+    };
+
     contract
         .tokens
         .update(deps.storage, &token_id, |token| -> StdResult<_> {
             match token {
                 Some(mut nft) => {
                     nft.extension = Metadata {
+                        parent_token_id,
                         ..Metadata::default()
                     };
                     Ok(nft)
@@ -530,26 +1606,197 @@ pub fn clear_metadata(deps: DepsMut, token_id: String) -> Result<(), ContractErr
                 None => Ok(username_nft),
             }
         })?;
+    bump_metadata_revision(deps.storage, &token_id)?;
     Ok(())
 }
 
 // this function burns all paths
-// that sit under a token
-pub fn burn_paths(deps: DepsMut, token_id: String) -> Result<(), ContractError> {
+// that sit under a token, however deeply nested
+// if owner is set, only the paths it still owns are burned
+// a path that has been sold on is kept, along with everything under it
+// if there are too many to burn in one go
+// the token is locked until ContinueBurnPaths finishes the job
+// returns true if every path has been burned
+pub fn burn_paths(
+    mut deps: DepsMut,
+    token_id: String,
+    owner: Option<Addr>,
+) -> Result<bool, ContractError> {
     let contract = Cw721MetadataContract::default();
-    let base_nft = contract.tokens.load(deps.storage, &token_id)?;
-    let owner_addr = base_nft.owner;
-
-    let paths =
-        get_paths_for_owner_and_token(deps.as_ref(), owner_addr.to_string(), token_id, None, None)?
-            .tokens;
 
-    for path_id in paths {
+    // leaves first, so we never orphan anything
+    // if we stop part way through
+    let paths = walk_paths_leaves_first(
+        deps.as_ref(),
+        &token_id,
+        PATH_BURN_BATCH_SIZE + 1,
+        |path_id| match owner {
+            Some(ref owner) => Ok(contract.tokens.load(deps.storage, path_id)?.owner != *owner),
+            None => Ok(false),
+        },
+    )?;
+    let complete = paths.len() <= PATH_BURN_BATCH_SIZE;
+
+    for path_id in paths.into_iter().take(PATH_BURN_BATCH_SIZE) {
+        clear_alias_if_primary(deps.branch(), path_id.clone())?;
+        let path = contract.tokens.load(deps.storage, &path_id)?;
+        remove_from_parent_index(deps.storage, &path_id, &path.extension.parent_token_id)?;
+        PATHS.remove(deps.storage, &path_id);
+        INHERIT_METADATA.remove(deps.storage, &path_id);
+        FUSES.remove(deps.storage, &path_id);
+        METADATA_REVISIONS.remove(deps.storage, &path_id);
+        move_records(deps.storage, &path_id, None)?;
+        rekey_contract_reverse_record(deps.storage, &path_id, None, &path.extension)?;
         contract.tokens.remove(deps.storage, &path_id)?;
         contract.decrement_tokens(deps.storage)?;
     }
 
-    Ok(())
+    if complete {
+        PENDING_PATH_BURNS.remove(deps.storage, &token_id);
+    } else {
+        PENDING_PATH_BURNS.save(deps.storage, &token_id, &owner)?;
+    }
+
+    Ok(complete)
+}
+
+// burns the next batch of paths under a token
+// left over from a burn, transfer or send
+// anyone can call this, as it only finishes what the owner started
+pub fn continue_burn_paths(
+    _contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let owner = PENDING_PATH_BURNS
+        .may_load(deps.storage, &token_id)?
+        .ok_or(ContractError::NoPathBurnPending {})?;
+
+    let complete = burn_paths(deps, token_id.clone(), owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "continue_burn_paths")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("paths_burned", complete.to_string()))
+}
+
+// backfills the indexes added since the last version
+// a batch at a time, so that it fits in a block however many tokens there are
+// counts are rebuilt from zero each time migrate is run
+// so running it twice gives the same result as running it once
+pub fn migrate_batch(
+    contract: Cw721MetadataContract,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let current_admin_address = contract.minter(deps.as_ref())?.minter;
+    ensure_eq!(
+        current_admin_address,
+        info.sender,
+        ContractError::Unauthorized {}
+    );
+
+    let mut progress = MIGRATION_PROGRESS
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoMigrationInProgress {})?;
+
+    let handled = match progress.step {
+        MigrationStep::ClearPathCounts => {
+            let roots = PATH_COUNTS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .take(MIGRATION_BATCH_SIZE)
+                .collect::<StdResult<Vec<_>>>()?;
+            for root in &roots {
+                PATH_COUNTS.remove(deps.storage, root);
+            }
+            if roots.len() < MIGRATION_BATCH_SIZE {
+                progress.step = MigrationStep::Tokens;
+            }
+            roots.len()
+        }
+        MigrationStep::Tokens => {
+            let tokens = contract
+                .tokens
+                .range(
+                    deps.storage,
+                    progress
+                        .start_after
+                        .clone()
+                        .map(|s| Bound::ExclusiveRaw(s.into_bytes())),
+                    None,
+                    Order::Ascending,
+                )
+                .take(MIGRATION_BATCH_SIZE)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (token_id, token) in &tokens {
+                // paths transferred before parents were kept on transfer lost theirs
+                // but a path's parent is always in its id
+                let mut parent_token_id = token.extension.parent_token_id.clone();
+                if is_path(token_id) && parent_token_id.is_none() {
+                    parent_token_id = get_parent_of_path(token_id).map(String::from);
+                    let mut token = token.clone();
+                    token.extension.parent_token_id = parent_token_id.clone();
+                    contract.tokens.save(deps.storage, token_id, &token)?;
+                }
+                if let Some(ref parent) = parent_token_id {
+                    PARENT_INDEX.save(deps.storage, (parent, token_id), &Empty {})?;
+                }
+                if is_path(token_id) {
+                    PATHS.save(deps.storage, token_id, &parse_path(token_id))?;
+                    PATH_COUNTS.update(
+                        deps.storage,
+                        get_root_of_path(token_id),
+                        |count| -> StdResult<_> { Ok(count.unwrap_or_default() + 1) },
+                    )?;
+                } else {
+                    progress.base_token_count += 1;
+                }
+            }
+            progress.start_after = tokens.last().map(|(token_id, _)| token_id.clone());
+            if tokens.len() < MIGRATION_BATCH_SIZE {
+                BASE_TOKEN_COUNT.save(deps.storage, &progress.base_token_count)?;
+                progress.step = MigrationStep::Aliases;
+                progress.start_after = None;
+            }
+            tokens.len()
+        }
+        MigrationStep::Aliases => {
+            let aliases = PRIMARY_ALIASES
+                .range(
+                    deps.storage,
+                    progress
+                        .start_after
+                        .clone()
+                        .map(|s| Bound::ExclusiveRaw(s.into_bytes())),
+                    None,
+                    Order::Ascending,
+                )
+                .take(MIGRATION_BATCH_SIZE)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (address, token_id) in &aliases {
+                set_primary_alias(deps.storage, address, token_id)?;
+            }
+            progress.start_after = aliases.last().map(|(address, _)| address.to_string());
+            if aliases.len() < MIGRATION_BATCH_SIZE {
+                MIGRATION_PROGRESS.remove(deps.storage);
+                return Ok(Response::new()
+                    .add_attribute("action", "migrate_batch")
+                    .add_attribute("handled", aliases.len().to_string())
+                    .add_attribute("done", "true"));
+            }
+            aliases.len()
+        }
+    };
+
+    MIGRATION_PROGRESS.save(deps.storage, &progress)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate_batch")
+        .add_attribute("handled", handled.to_string())
+        .add_attribute("done", "false"))
 }
 
 pub fn transfer_nft(
@@ -563,23 +1810,35 @@ pub fn transfer_nft(
     // check permissions before proceeding
     let token = contract.tokens.load(deps.storage, &token_id)?;
     contract.check_can_send(deps.as_ref(), &env, &info, &token)?;
+    ensure_no_pending_path_burn(deps.as_ref(), &token_id)?;
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_transfer)?;
 
     // clear aliases before transfer iif it is the one being xfrd
     clear_alias_if_primary(deps.branch(), token_id.to_string())?;
+    rekey_contract_reverse_record(deps.storage, &token_id, None, &token.extension)?;
 
     // blank meta before xfer
-    clear_metadata(deps.branch(), token_id.to_string())?;
+    // unless it has been fused
+    if !get_fuses(deps.storage, &token_id)?.cannot_update_metadata {
+        clear_metadata(deps.branch(), token_id.to_string())?;
+    }
 
-    // clear paths
-    burn_paths(deps.branch(), token_id.to_string())?;
+    // clear the paths that go with it
+    let paths_burned = burn_paths(deps.branch(), token_id.to_string(), Some(token.owner))?;
 
-    contract._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+    let set_alias = should_set_alias_on_receive(&contract, deps.as_ref(), &recipient, &token_id)?;
+    contract._transfer_nft(deps.branch(), &env, &info, &recipient, &token_id)?;
+    if set_alias {
+        let recipient_addr = deps.api.addr_validate(&recipient)?;
+        set_primary_alias(deps.storage, &recipient_addr, &token_id)?;
+    }
 
     Ok(Response::new()
         .add_attribute("action", "transfer_nft")
         .add_attribute("sender", info.sender)
         .add_attribute("recipient", recipient)
-        .add_attribute("token_id", token_id))
+        .add_attribute("token_id", token_id)
+        .add_attribute("paths_burned", paths_burned.to_string()))
 }
 
 pub fn send_nft(
@@ -594,18 +1853,30 @@ pub fn send_nft(
     // check permissions before proceeding
     let token = contract.tokens.load(deps.storage, &token_id)?;
     contract.check_can_send(deps.as_ref(), &env, &info, &token)?;
+    ensure_no_pending_path_burn(deps.as_ref(), &token_id)?;
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_transfer)?;
 
     // clear aliases before send iif it is the one being sent
     clear_alias_if_primary(deps.branch(), token_id.to_string())?;
+    rekey_contract_reverse_record(deps.storage, &token_id, None, &token.extension)?;
 
     // blank meta before send
-    clear_metadata(deps.branch(), token_id.to_string())?;
+    // unless it has been fused
+    if !get_fuses(deps.storage, &token_id)?.cannot_update_metadata {
+        clear_metadata(deps.branch(), token_id.to_string())?;
+    }
 
-    // clear paths
-    burn_paths(deps.branch(), token_id.to_string())?;
+    // clear the paths that go with it
+    let paths_burned = burn_paths(deps.branch(), token_id.to_string(), Some(token.owner))?;
 
     // Transfer token
-    contract._transfer_nft(deps, &env, &info, &receiving_contract, &token_id)?;
+    let set_alias =
+        should_set_alias_on_receive(&contract, deps.as_ref(), &receiving_contract, &token_id)?;
+    contract._transfer_nft(deps.branch(), &env, &info, &receiving_contract, &token_id)?;
+    if set_alias {
+        let receiving_addr = deps.api.addr_validate(&receiving_contract)?;
+        set_primary_alias(deps.storage, &receiving_addr, &token_id)?;
+    }
 
     let send = Cw721ReceiveMsg {
         sender: info.sender.to_string(),
@@ -619,7 +1890,8 @@ pub fn send_nft(
         .add_attribute("action", "send_nft")
         .add_attribute("sender", info.sender)
         .add_attribute("recipient", receiving_contract)
-        .add_attribute("token_id", token_id))
+        .add_attribute("token_id", token_id)
+        .add_attribute("paths_burned", paths_burned.to_string()))
 }
 
 pub fn burn(
@@ -631,18 +1903,45 @@ pub fn burn(
 ) -> Result<Response, ContractError> {
     let token = contract.tokens.load(deps.storage, &token_id)?;
     contract.check_can_send(deps.as_ref(), &env, &info, &token)?;
+    ensure_no_pending_path_burn(deps.as_ref(), &token_id)?;
+    ensure_fuse_not_burned(deps.as_ref(), &token_id, |f| f.cannot_burn)?;
 
     // clear aliases before delete iif it is the one being burned
     clear_alias_if_primary(deps.branch(), token_id.to_string())?;
 
-    // clear paths
-    burn_paths(deps.branch(), token_id.to_string())?;
-
+    // clear paths, whoever owns them
+    let paths_burned = burn_paths(deps.branch(), token_id.to_string(), None)?;
+
+    remove_from_parent_index(deps.storage, &token_id, &token.extension.parent_token_id)?;
+    PATHS.remove(deps.storage, &token_id);
+    NAMESPACE_FEES.remove(deps.storage, &token_id);
+    INHERIT_METADATA.remove(deps.storage, &token_id);
+    FUSES.remove(deps.storage, &token_id);
+    METADATA_REVISIONS.remove(deps.storage, &token_id);
+    move_records(deps.storage, &token_id, None)?;
+    rekey_contract_reverse_record(deps.storage, &token_id, None, &token.extension)?;
     contract.tokens.remove(deps.storage, &token_id)?;
     contract.decrement_tokens(deps.storage)?;
+    if !is_path(&token_id) {
+        let base_token_count = BASE_TOKEN_COUNT.may_load(deps.storage)?.unwrap_or_default();
+        BASE_TOKEN_COUNT.save(deps.storage, &base_token_count.saturating_sub(1))?;
+    }
+
+    // return any deposit to the owner
+    let mut refund_msgs = vec![];
+    if let Some(deposit) = DEPOSITS.may_load(deps.storage, &token_id)? {
+        DEPOSITS.remove(deps.storage, &token_id);
+        let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+        refund_msgs.push(BankMsg::Send {
+            to_address: token.owner.to_string(),
+            amount: coins(deposit.u128(), minting_fees.native_denom),
+        });
+    }
 
     Ok(Response::new()
+        .add_messages(refund_msgs)
         .add_attribute("action", "burn")
         .add_attribute("sender", info.sender)
-        .add_attribute("token_id", token_id))
+        .add_attribute("token_id", token_id)
+        .add_attribute("paths_burned", paths_burned.to_string()))
 }
@@ -1,3 +1,4 @@
+mod bech32;
 mod contract_tests;
 mod error;
 pub mod execute;
@@ -5,19 +6,28 @@ pub mod msg;
 pub mod query;
 pub mod state;
 pub mod utils;
+pub mod validation;
 
 use cosmwasm_std::{ensure_eq, to_binary, Empty};
 
 use cw2::set_contract_version;
 use execute::{
-    burn, execute_instantiate, mint, mint_path, send_nft, set_admin_address,
-    set_username_length_cap, transfer_nft, update_metadata, update_minting_fees,
-    update_primary_alias, CONTRACT_NAME, CONTRACT_VERSION,
+    burn, burn_fuses, continue_burn_paths, execute_instantiate, migrate_batch, mint, mint_path,
+    move_path, patch_metadata, remove_address_record, remove_text_record, rename, send_nft,
+    set_address_record, set_admin_address, set_contract_reverse_record, set_text_record,
+    set_username_length_cap, transfer_nft, update_metadata, update_metadata_inheritance,
+    update_minting_fees, update_name_categories, update_namespace_fee, update_path_limits,
+    update_primary_alias, update_primary_alias_policy, update_rate_limits,
+    update_text_record_limits, CONTRACT_NAME, CONTRACT_VERSION,
 };
 use query::{
-    address_of, contract_info, get_base_tokens_for_owner, get_parent_id, get_parent_nft_info,
-    get_path, get_paths_for_owner, get_paths_for_owner_and_token, is_contract, list_info_by_alias,
-    primary_alias,
+    address_of, address_of_on_chain, all_primary_aliases, contract_info, deposit,
+    derived_addresses, fuses, get_base_tokens_for_owner, get_children, get_parent_id,
+    get_parent_nft_info, get_path, get_paths_for_owner, get_paths_for_owner_and_token, get_tree,
+    is_contract, list_info_by_alias, metadata_revision, mint_allowance, mint_price,
+    name_categories, path_limits, path_mint_price, primary_alias, primary_alias_addresses,
+    primary_alias_policy, primary_aliases, profile, rate_limits, resolve_path, resolved_metadata,
+    text_record, text_record_limits, text_records,
 };
 
 pub use crate::msg::{ContractInfo, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg};
@@ -29,7 +39,9 @@ pub type Cw721MetadataContract<'a> = cw721_base::Cw721Contract<'a, Extension, Em
 pub mod entry {
 
     use super::*;
-    use crate::state::{CONTRACT_INFO, LEGACY_CONTRACT_INFO};
+    use crate::state::{
+        MigrationProgress, MigrationStep, CONTRACT_INFO, LEGACY_CONTRACT_INFO, MIGRATION_PROGRESS,
+    };
 
     use cosmwasm_std::entry_point;
     use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
@@ -53,17 +65,74 @@ pub mod entry {
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
         let tract = Cw721MetadataContract::default();
+
+        // nothing else can run until the indexes are backfilled
+        if MIGRATION_PROGRESS.may_load(deps.storage)?.is_some()
+            && !matches!(msg, ExecuteMsg::MigrateBatch {})
+        {
+            return Err(ContractError::MigrationInProgress {});
+        }
+
         match msg {
             ExecuteMsg::UpdateMintingFees(msg) => update_minting_fees(tract, deps, env, info, msg),
             ExecuteMsg::UpdateUsernameLengthCap { new_length } => {
                 set_username_length_cap(tract, deps, env, info, new_length)
             }
+            ExecuteMsg::UpdateNamespaceFee { token_id, fee } => {
+                update_namespace_fee(tract, deps, env, info, token_id, fee)
+            }
+            ExecuteMsg::UpdatePathLimits(msg) => update_path_limits(tract, deps, env, info, msg),
+            ExecuteMsg::UpdateRateLimits(msg) => update_rate_limits(tract, deps, env, info, msg),
+            ExecuteMsg::UpdateTextRecordLimits(msg) => {
+                update_text_record_limits(tract, deps, env, info, msg)
+            }
+            ExecuteMsg::SetTextRecord {
+                token_id,
+                key,
+                value,
+            } => set_text_record(tract, deps, env, info, token_id, key, value),
+            ExecuteMsg::RemoveTextRecord { token_id, key } => {
+                remove_text_record(tract, deps, env, info, token_id, key)
+            }
+            ExecuteMsg::SetAddressRecord {
+                token_id,
+                chain,
+                address,
+            } => set_address_record(tract, deps, env, info, token_id, chain, address),
+            ExecuteMsg::RemoveAddressRecord { token_id, chain } => {
+                remove_address_record(tract, deps, env, info, token_id, chain)
+            }
+            ExecuteMsg::UpdateNameCategories { categories } => {
+                update_name_categories(tract, deps, env, info, categories)
+            }
+            ExecuteMsg::Rename {
+                token_id,
+                new_token_id,
+            } => rename(tract, deps, env, info, token_id, new_token_id),
+            ExecuteMsg::BurnFuses { token_id, fuses } => {
+                burn_fuses(tract, deps, env, info, token_id, fuses)
+            }
+            ExecuteMsg::UpdateMetadataInheritance { token_id, inherit } => {
+                update_metadata_inheritance(tract, deps, env, info, token_id, inherit)
+            }
             ExecuteMsg::Mint(msg) => mint(tract, deps, env, info, msg),
             ExecuteMsg::MintPath(msg) => mint_path(tract, deps, env, info, msg),
+            ExecuteMsg::MovePath {
+                token_id,
+                new_parent,
+                new_name,
+            } => move_path(tract, deps, env, info, token_id, new_parent, new_name),
             ExecuteMsg::UpdateMetadata(msg) => update_metadata(tract, deps, env, info, msg),
+            ExecuteMsg::PatchMetadata(msg) => patch_metadata(tract, deps, env, info, msg),
             ExecuteMsg::UpdatePrimaryAlias { token_id } => {
                 update_primary_alias(tract, deps, env, info, token_id)
             }
+            ExecuteMsg::UpdatePrimaryAliasPolicy { policy } => {
+                update_primary_alias_policy(tract, deps, env, info, policy)
+            }
+            ExecuteMsg::SetContractReverseRecord { contract, token_id } => {
+                set_contract_reverse_record(tract, deps, env, info, contract, token_id)
+            }
             // this actually sets the minter field,
             // but the interface is that we call it an admin_address
             ExecuteMsg::SetAdminAddress { admin_address } => {
@@ -81,6 +150,10 @@ pub mod entry {
                 msg,
             } => send_nft(tract, deps, env, info, contract, token_id, msg),
             ExecuteMsg::Burn { token_id } => burn(tract, deps, env, info, token_id),
+            ExecuteMsg::ContinueBurnPaths { token_id } => {
+                continue_burn_paths(tract, deps, env, info, token_id)
+            }
+            ExecuteMsg::MigrateBatch {} => migrate_batch(tract, deps, env, info),
 
             _ => tract
                 .execute(deps, env, info, msg.into())
@@ -107,9 +180,60 @@ pub mod entry {
             QueryMsg::PrimaryAlias { address } => {
                 to_binary(&primary_alias(tract, deps, env, address)?)
             }
+            QueryMsg::PrimaryAliases { addresses } => {
+                to_binary(&primary_aliases(tract, deps, addresses)?)
+            }
+            QueryMsg::Profile { address } => to_binary(&profile(tract, deps, address)?),
+            QueryMsg::PrimaryAliasPolicy { address } => {
+                to_binary(&primary_alias_policy(deps, address)?)
+            }
+            QueryMsg::AllPrimaryAliases { start_after, limit } => {
+                to_binary(&all_primary_aliases(deps, start_after, limit)?)
+            }
+            QueryMsg::PrimaryAliasAddresses {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&primary_alias_addresses(
+                deps,
+                token_id,
+                start_after,
+                limit,
+            )?),
             QueryMsg::ContractInfo {} => to_binary(&contract_info(deps)?),
+            QueryMsg::PathLimits {} => to_binary(&path_limits(deps)?),
+            QueryMsg::RateLimits {} => to_binary(&rate_limits(deps)?),
+            QueryMsg::TextRecordLimits {} => to_binary(&text_record_limits(deps)?),
+            QueryMsg::TextRecord { token_id, key } => {
+                to_binary(&text_record(tract, deps, token_id, key)?)
+            }
+            QueryMsg::TextRecords {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&text_records(tract, deps, token_id, start_after, limit)?),
+            QueryMsg::MintAllowance { address } => to_binary(&mint_allowance(deps, env, address)?),
+            QueryMsg::Deposit { token_id } => to_binary(&deposit(tract, deps, token_id)?),
+            QueryMsg::Fuses { token_id } => to_binary(&fuses(tract, deps, token_id)?),
+            QueryMsg::ResolvedMetadata { token_id } => {
+                to_binary(&resolved_metadata(tract, deps, token_id)?)
+            }
+            QueryMsg::MintPrice { token_id } => to_binary(&mint_price(deps, token_id)?),
+            QueryMsg::NameCategories {} => to_binary(&name_categories(deps)?),
+            QueryMsg::PathMintPrice { parent_token_id } => {
+                to_binary(&path_mint_price(deps, parent_token_id)?)
+            }
             QueryMsg::IsContract { token_id } => to_binary(&is_contract(tract, deps, token_id)?),
             QueryMsg::AddressOf { token_id } => to_binary(&address_of(tract, deps, token_id)?),
+            QueryMsg::MetadataRevision { token_id } => {
+                to_binary(&metadata_revision(tract, deps, token_id)?)
+            }
+            QueryMsg::AddressOfOnChain { token_id, chain } => {
+                to_binary(&address_of_on_chain(tract, deps, token_id, chain)?)
+            }
+            QueryMsg::DerivedAddresses { token_id, prefixes } => {
+                to_binary(&derived_addresses(tract, deps, token_id, prefixes)?)
+            }
             QueryMsg::GetParentId { token_id } => to_binary(&get_parent_id(tract, deps, token_id)?),
             QueryMsg::GetParentInfo { token_id } => {
                 to_binary(&get_parent_nft_info(tract, deps, token_id)?)
@@ -141,7 +265,28 @@ pub mod entry {
             QueryMsg::ListInfoByAlias { aliases } => {
                 to_binary(&list_info_by_alias(tract, deps, aliases)?)
             }
-            _ => tract.query(deps, env, msg.into()).map_err(|err| err),
+            QueryMsg::Children {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&get_children(deps, token_id, start_after, limit)?),
+            QueryMsg::ResolvePath { path } => to_binary(&resolve_path(tract, deps, path)?),
+            QueryMsg::Tree {
+                token_id,
+                max_depth,
+                limit,
+                start_after,
+                include_metadata,
+            } => to_binary(&get_tree(
+                tract,
+                deps,
+                token_id,
+                max_depth,
+                limit,
+                start_after,
+                include_metadata,
+            )?),
+            _ => tract.query(deps, env, msg.into()),
         }
     }
 
@@ -153,14 +298,28 @@ pub mod entry {
             ContractError::Unauthorized {}
         );
 
-        let contract_info = LEGACY_CONTRACT_INFO.load(deps.storage)?;
-        let info = ContractInfo {
-            name: contract_info.name,
-            symbol: "DENS".to_string(),
-        };
         // in this version of the code
         // the storage key has been changed
-        CONTRACT_INFO.save(deps.storage, &info)?;
+        // cw2 reuses the old key afterwards, so this is only done once
+        if CONTRACT_INFO.may_load(deps.storage)?.is_none() {
+            let contract_info = LEGACY_CONTRACT_INFO.load(deps.storage)?;
+            let info = ContractInfo {
+                name: contract_info.name,
+                symbol: "DENS".to_string(),
+            };
+            CONTRACT_INFO.save(deps.storage, &info)?;
+        }
+
+        // the parent index, path structure, counts and alias index
+        // are backfilled by MigrateBatch, a batch at a time
+        MIGRATION_PROGRESS.save(
+            deps.storage,
+            &MigrationProgress {
+                step: MigrationStep::ClearPathCounts,
+                start_after: None,
+                base_token_count: 0,
+            },
+        )?;
 
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
         Ok(Response::new().add_attribute("action", "migrate"))
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Decimal, Uint128};
 use cw20::Logo;
 use cw721::{Expiration, NftInfoResponse};
 use cw721_base::{
@@ -20,6 +20,55 @@ pub struct SurchargeInfo {
     pub surcharge_fee: Uint128,
 }
 
+/// Special patterns of name that can be priced separately
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NameCategory {
+    /// Only digits, e.g. 007
+    Numeric,
+    /// Reads the same backwards, e.g. abba
+    Palindrome,
+    /// A single character repeated, e.g. zzz
+    RepeatedCharacter,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NameCategoryPricing {
+    pub category: NameCategory,
+    /// Whether names in this category can be minted at all
+    pub enabled: bool,
+    /// Applied to the mint fee (base fee plus any surcharge)
+    /// for names in this category
+    /// e.g. 2 doubles it, 0.5 halves it
+    pub fee_multiplier: Decimal,
+}
+
+/// Raises the base mint fee as more base names are registered
+/// so early adopters pay less
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondingCurve {
+    /// How many base names are registered between each increase
+    /// e.g. 1000
+    pub base_names_per_step: u64,
+    /// How much the base mint fee goes up at each step
+    /// this is assumed to be in native_denom
+    pub fee_increase_per_step: Uint128,
+    /// The base mint fee never goes above this, if set
+    pub max_base_mint_fee: Option<Uint128>,
+}
+
+/// How the mint fee for a base name is handled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationModel {
+    /// The fee is split between the admin_address and burning
+    Fee,
+    /// The fee is held by the contract as a deposit
+    /// it stays with the name when it is transferred
+    /// and is returned to the owner when the name is burned
+    Deposit,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
     /// Specify the version that we are migrating up to
@@ -68,6 +117,10 @@ pub struct InstantiateMsg {
     /// The cap for a username length
     /// can be updated later by the admin_address
     pub username_length_cap: Option<u32>,
+
+    /// Whether the mint fee is paid out or held as a refundable deposit
+    /// defaults to fee, and can't be changed later
+    pub registration_model: Option<RegistrationModel>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -112,6 +165,151 @@ pub struct UpdateMetadataMsg {
     pub metadata: Metadata,
 }
 
+/// A change to a single metadata field
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldUpdate<T> {
+    Set(T),
+    Clear,
+}
+
+/// Any field left unset is unchanged
+/// parent_token_id can't be changed
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PatchMetadataMsg {
+    pub token_id: String,
+    /// If set, the patch is rejected unless the metadata
+    /// is still at this revision
+    pub expected_revision: Option<u64>,
+    pub image: Option<FieldUpdate<String>>,
+    pub image_data: Option<FieldUpdate<Logo>>,
+    pub email: Option<FieldUpdate<String>>,
+    pub external_url: Option<FieldUpdate<String>>,
+    pub public_name: Option<FieldUpdate<String>>,
+    pub public_bio: Option<FieldUpdate<String>>,
+    pub twitter_id: Option<FieldUpdate<String>>,
+    pub discord_id: Option<FieldUpdate<String>>,
+    pub telegram_id: Option<FieldUpdate<String>>,
+    pub keybase_id: Option<FieldUpdate<String>>,
+    pub validator_operator_address: Option<FieldUpdate<String>>,
+    pub contract_address: Option<FieldUpdate<String>>,
+    pub pgp_public_key: Option<FieldUpdate<String>>,
+}
+
+/// What happens to an address's primary alias when that name
+/// is transferred, sent or burned
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimaryAliasPolicy {
+    /// The alias is cleared, and the first token owned is used instead
+    #[default]
+    FirstToken,
+    /// The first of these still owned becomes the primary alias
+    /// if none are, there is no alias
+    Backups { aliases: Vec<String> },
+    /// The alias is cleared, and there is no alias until one is set
+    None,
+}
+
+/// Permissions that can be irreversibly burned on a token
+/// true means the fuse is burned and the action is no longer possible
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Fuses {
+    /// The token can no longer be transferred or sent
+    /// if it is a path, it can no longer be moved
+    pub cannot_transfer: bool,
+    /// The metadata can no longer be updated
+    /// and will not be cleared on transfer
+    pub cannot_update_metadata: bool,
+    /// No more paths can be minted directly under the token
+    pub cannot_mint_paths: bool,
+    /// The token can no longer be burned
+    pub cannot_burn: bool,
+}
+
+/// Limits on paths, set by the contract admin
+/// Any field left unset is unlimited
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PathLimits {
+    /// How many levels a path can be nested below its base token
+    /// e.g. 2 allows jeffvader::vehicles::tie-fighter but nothing under it
+    pub max_depth: Option<u32>,
+    /// How many paths can sit under a single base token, however nested
+    pub max_paths_per_namespace: Option<u32>,
+    /// Max characters in a single segment of a path
+    pub max_segment_length: Option<u32>,
+}
+
+/// Limits on how quickly names and paths can be minted
+/// set by the contract admin
+/// Any field left unset is unlimited
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct RateLimits {
+    /// How many mints a single address can make
+    /// within window_blocks
+    pub max_mints_per_address: Option<u32>,
+    /// How many blocks the window for max_mints_per_address covers,
+    /// including the current block. Defaults to 1
+    pub window_blocks: Option<u64>,
+    /// How many mints can be made in a single block, by anyone
+    pub max_mints_per_block: Option<u32>,
+}
+
+/// The chain an address record is for
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    /// A Cosmos chain, by its bech32 prefix, e.g. osmo
+    Bech32 { prefix: String },
+    /// A coin type registered in SLIP-44, e.g. 60 for ether
+    Slip44 { coin_type: u32 },
+    /// An EVM chain, by its chain id, e.g. 1 for Ethereum mainnet
+    Evm { chain_id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AddressRecord {
+    pub chain: Chain,
+    pub address: String,
+}
+
+/// Where an address on another chain came from
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressSource {
+    /// Set with SetAddressRecord
+    Explicit,
+    /// The owner's address, re-encoded for the chain
+    Derived,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DerivedAddress {
+    pub prefix: String,
+    pub address: String,
+    pub source: AddressSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DerivedAddressesResponse {
+    pub addresses: Vec<DerivedAddress>,
+}
+
+/// Limits on text records, set by the contract admin
+/// Any field left unset uses the default: keys of up to 64 characters,
+/// values of up to 1024 and 20 records per token
+/// None of them can be raised above the default
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct TextRecordLimits {
+    /// Max characters in a key
+    pub max_key_length: Option<u32>,
+    /// Max characters in a value
+    pub max_value_length: Option<u32>,
+    /// How many text records a single token can have
+    /// not counting the well-known keys
+    pub max_records: Option<u32>,
+}
+
 /// This can only be done by the contract admin
 /// Note that these fields will forcibly update what is already set
 /// You must be declarative and specify exactly the new desired behaviour
@@ -121,6 +319,13 @@ pub struct UpdateMintingFeesMsg {
     pub base_mint_fee: Option<Uint128>,
     pub burn_percentage: Option<u64>,
     pub short_name_surcharge: Option<SurchargeInfo>,
+    /// An optional fee for minting a path
+    /// split between the admin_address and burning
+    /// in the same way as base_mint_fee
+    pub path_mint_fee: Option<Uint128>,
+    /// An optional curve that raises base_mint_fee
+    /// with the number of base names registered
+    pub bonding_curve: Option<BondingCurve>,
 }
 
 // Extended CW721 ExecuteMsg, added the ability to update, burn, and finalize nft
@@ -133,15 +338,123 @@ pub enum ExecuteMsg {
     /// Updates the username length cap
     UpdateUsernameLengthCap { new_length: u32 },
 
+    /// Sets a fee for minting paths under a base token
+    /// paid to the owner of that base token
+    /// This can only be done by the owner of the base token
+    /// Unset to remove the fee
+    UpdateNamespaceFee {
+        token_id: String,
+        fee: Option<Uint128>,
+    },
+
+    /// Updates the limits on paths
+    /// This can only be done by the contract admin
+    /// Note that these fields will forcibly update what is already set
+    UpdatePathLimits(PathLimits),
+
+    /// Updates the limits on how quickly tokens can be minted
+    /// This can only be done by the contract admin
+    /// Note that these fields will forcibly update what is already set
+    UpdateRateLimits(RateLimits),
+
+    /// Updates the limits on the size and number of text records
+    /// This can only be done by the contract admin
+    /// Note that these fields will forcibly update what is already set
+    UpdateTextRecordLimits(TextRecordLimits),
+
+    /// Updates the name categories that are priced separately
+    /// A name is priced by the first category in the list it matches
+    /// This can only be done by the contract admin
+    /// Note that this will forcibly replace what is already set
+    UpdateNameCategories {
+        categories: Vec<NameCategoryPricing>,
+    },
+
+    /// Opts a token in or out of inheriting metadata
+    /// Any field left unset on the token falls back
+    /// to the nearest ancestor that has it set
+    /// This can only be done by the owner of the token
+    UpdateMetadataInheritance { token_id: String, inherit: bool },
+
+    /// Renames a base token, along with every path under it
+    /// Metadata, approvals and the owner's primary alias are kept
+    /// Any increase in minting fee for the new name must be paid
+    /// This can only be done by the owner of the token
+    /// It happens in one go, there is no continuation like ContinueBurnPaths,
+    /// so a name with more than 50 paths and nested base tokens under it
+    /// is rejected with TooManyPathsToMove
+    Rename {
+        token_id: String,
+        new_token_id: String,
+    },
+
+    /// Irreversibly burns permissions on a token
+    /// Any fuse set to true is burned, and can never be unburned
+    /// Fuses can only be burned on a path once its parent
+    /// can no longer be transferred or burned
+    /// This can only be done by the owner of the token
+    BurnFuses { token_id: String, fuses: Fuses },
+
     /// If the sender has multiple NFTs or aliases, they will want to set a default. This allows them to do this.
     UpdatePrimaryAlias { token_id: String },
 
+    /// Sets what happens to the sender's primary alias
+    /// when that name is transferred, sent or burned
+    /// Backup aliases must be owned by the sender, at most 10 can be set
+    UpdatePrimaryAliasPolicy { policy: PrimaryAliasPolicy },
+
+    /// Sets a text record on a token, overwriting any existing value
+    /// Keys are lowercase letters, numbers, '.', '_' and '-'
+    /// Well-known keys are read from the metadata, so can't be set
+    /// This can only be done by the owner of the token
+    SetTextRecord {
+        token_id: String,
+        key: String,
+        value: String,
+    },
+
+    /// Removes a text record from a token
+    /// This can only be done by the owner of the token
+    RemoveTextRecord { token_id: String, key: String },
+
+    /// Sets the address a token resolves to on another chain
+    /// The address is checked against the format for the chain
+    /// At most 20 can be set on a token
+    /// This can only be done by the owner of the token
+    SetAddressRecord {
+        token_id: String,
+        chain: Chain,
+        address: String,
+    },
+
+    /// Removes the address a token resolves to on another chain
+    /// This can only be done by the owner of the token
+    RemoveAddressRecord { token_id: String, chain: Chain },
+
+    /// Sets the reverse record for a contract, which can't set its own primary alias
+    /// The name's contract_address must point at the contract
+    /// This can only be done by the contract's wasm admin
+    SetContractReverseRecord { contract: String, token_id: String },
+
     /// Updates the metadata of the NFT
     UpdateMetadata(UpdateMetadataMsg),
 
+    /// Updates only the given fields of the metadata of the NFT
+    /// Each field can be left unchanged, set or cleared
+    PatchMetadata(PatchMetadataMsg),
+
     /// Destroys the NFT permanently.
     Burn { token_id: String },
 
+    /// Burns the next batch of paths left under a token
+    /// by a Burn, TransferNft or SendNft that had too many to burn at once.
+    /// The token is locked until this has been called enough times to clear them all.
+    ContinueBurnPaths { token_id: String },
+
+    /// Admin only - backfills the next batch of indexes after a migration.
+    /// Everything else is rejected until this has been called enough times to finish.
+    MigrateBatch {},
+
     /// Set admin
     SetAdminAddress { admin_address: String },
 
@@ -158,6 +471,19 @@ pub enum ExecuteMsg {
     /// jeffvader::construction-projects::...
     MintPath(MintMsg),
 
+    /// Move a path under a new parent, give it a new name, or both
+    /// e.g. jeffvader::vehicles::tie-fighter
+    /// with a new_parent of jeffvader::fleet and a new_name of tie-interceptor
+    /// becomes jeffvader::fleet::tie-interceptor
+    /// Everything under the path moves with it.
+    /// The sender must own the path, its current parent and the new parent.
+    /// If either field is unset it is left as it is.
+    MovePath {
+        token_id: String,
+        new_parent: Option<String>,
+        new_name: Option<String>,
+    },
+
     // Standard CW721 ExecuteMsg
     /// Transfer is a base message to move a token to another account without triggering actions
     TransferNft { recipient: String, token_id: String },
@@ -233,6 +559,35 @@ pub enum QueryMsg {
     /// Query preferred alias. Takes an address and returns the token id, if set.
     /// default behaviour is to return the first result, if unset.
     PrimaryAlias { address: String },
+    /// Query preferred aliases for many addresses at once.
+    /// Returns an entry per address, in the same order.
+    /// Addresses without an alias (or that are invalid) get an entry with no username,
+    /// rather than failing the whole query
+    /// At most 100 addresses can be looked up at once
+    /// Return type: PrimaryAliasesResponse
+    PrimaryAliases { addresses: Vec<String> },
+    /// The preferred alias for an address, along with its owner,
+    /// metadata, full path and where the alias came from.
+    /// Saves following PrimaryAlias with NftInfo and GetFullPath
+    /// Return type: ProfileResponse
+    Profile { address: String },
+    /// What happens to an address's primary alias
+    /// when that name leaves the account
+    /// Return type: PrimaryAliasPolicy
+    PrimaryAliasPolicy { address: String },
+    /// Every address that has explicitly set a primary alias, ordered by address
+    /// Return type: AllPrimaryAliasesResponse
+    AllPrimaryAliases {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The addresses that have explicitly set token_id as their primary alias
+    /// Return type: AddressesResponse
+    PrimaryAliasAddresses {
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Return the owner of the given token, error if token does not exist
     /// Return type: OwnerOfResponse
     OwnerOf {
@@ -243,6 +598,21 @@ pub enum QueryMsg {
     /// Query address of a name
     /// This returns contract address if contract
     AddressOf { token_id: String },
+    /// Query the address of a name on another chain
+    /// Return type: AddressRecord
+    AddressOfOnChain { token_id: String, chain: Chain },
+    /// How many times the metadata of a token has been changed
+    /// Return type: MetadataRevisionResponse
+    MetadataRevision { token_id: String },
+    /// The addresses of a name on other Cosmos chains, one per prefix
+    /// An address record for the prefix is used if there is one,
+    /// otherwise the owner's address is re-encoded with the prefix
+    /// At most 30 prefixes can be looked up at once
+    /// Return type: DerivedAddressesResponse
+    DerivedAddresses {
+        token_id: String,
+        prefixes: Vec<String>,
+    },
     /// List all operators that can access all of the owner's tokens.
     /// Return type: `OperatorsResponse`
     AllOperators {
@@ -299,6 +669,63 @@ pub enum QueryMsg {
     /// Return the admin address
     AdminAddress {},
 
+    /// Return what it costs to mint token_id as a base token
+    /// Return type: MintPriceResponse
+    MintPrice { token_id: String },
+
+    /// Return the name categories that are priced separately
+    /// Return type: NameCategoriesResponse
+    NameCategories {},
+
+    /// Return what it costs to mint a path under parent_token_id
+    /// Return type: PathMintPriceResponse
+    PathMintPrice { parent_token_id: String },
+
+    /// Return the limits on paths
+    /// Return type: PathLimits
+    PathLimits {},
+
+    /// Return the limits on how quickly tokens can be minted
+    /// Return type: RateLimits
+    RateLimits {},
+
+    /// Return the limits on text records
+    /// Return type: TextRecordLimits
+    TextRecordLimits {},
+
+    /// A single text record on a token
+    /// well-known keys are read from the token's metadata
+    /// Return type: TextRecordResponse
+    TextRecord { token_id: String, key: String },
+
+    /// The text records on a token, ordered by key
+    /// including any well-known keys that are set in the metadata
+    /// Return type: TextRecordsResponse
+    TextRecords {
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Return how many more mints an address can make
+    /// in the current window and block
+    /// Return type: MintAllowanceResponse
+    MintAllowance { address: String },
+
+    /// Return the metadata of a token merged with its ancestors'
+    /// if it inherits metadata, along with where each field came from
+    /// Return type: ResolvedMetadataResponse
+    ResolvedMetadata { token_id: String },
+
+    /// Return the deposit held for a token
+    /// in the deposit registration model
+    /// Return type: DepositResponse
+    Deposit { token_id: String },
+
+    /// Return the fuses burned on a token
+    /// Return type: Fuses
+    Fuses { token_id: String },
+
     /// Return if this is an executable contract or not
     IsContract { token_id: String },
 
@@ -336,6 +763,36 @@ pub enum QueryMsg {
     /// Returns token info for a list of token IDs
     /// Includes owner and token metadata
     ListInfoByAlias { aliases: Vec<String> },
+
+    /// Returns the token_ids of every token whose parent is token_id
+    /// i.e. paths and any base tokens nested under it, but not their children
+    /// [] if none.
+    /// Return type: TokensResponse.
+    Children {
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// The reverse of GetFullPath
+    /// takes a full path like jeffvader/vehicles::tie-fighter
+    /// and returns the token it points to, checking every link on the way
+    /// Return type: ResolvePathResponse
+    ResolvePath { path: String },
+
+    /// Returns everything nested under token_id as a tree
+    /// paths and base tokens alike, down to max_depth
+    /// If there are more than limit nodes, pass next_start_after
+    /// from the response as start_after to get the rest.
+    /// Return type: TreeResponse
+    Tree {
+        token_id: String,
+        max_depth: Option<u32>,
+        limit: Option<u32>,
+        start_after: Option<String>,
+        /// unset or false will leave out metadata for each node
+        include_metadata: Option<bool>,
+    },
 }
 
 impl From<QueryMsg> for CW721QueryMsg {
@@ -392,6 +849,57 @@ pub struct PrimaryAliasResponse {
     pub username: String,
 }
 
+/// Where a primary alias came from
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimaryAliasSource {
+    /// Set with UpdatePrimaryAlias
+    Explicit,
+    /// Set for a contract with SetContractReverseRecord
+    ContractRecord,
+    /// Not set, so the first base token owned is used
+    FirstToken,
+    /// The address has no alias
+    None,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PrimaryAliasEntry {
+    pub address: String,
+    pub username: Option<String>,
+    pub source: PrimaryAliasSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PrimaryAliasesResponse {
+    pub aliases: Vec<PrimaryAliasEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PrimaryAliasRecord {
+    pub address: String,
+    pub username: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllPrimaryAliasesResponse {
+    pub aliases: Vec<PrimaryAliasRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AddressesResponse {
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProfileResponse {
+    pub username: String,
+    pub owner: String,
+    pub metadata: Metadata,
+    pub path: String,
+    pub source: PrimaryAliasSource,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ContractInfo {
     pub name: String,
@@ -408,6 +916,13 @@ pub struct ContractInfoResponse {
     pub base_mint_fee: Option<Uint128>,
     pub burn_percentage: Option<u64>,
     pub short_name_surcharge: Option<SurchargeInfo>,
+    pub path_mint_fee: Option<Uint128>,
+    pub bonding_curve: Option<BondingCurve>,
+    /// How many base names are registered
+    pub base_token_count: u64,
+    /// The base mint fee right now, with any bonding curve applied
+    pub current_base_mint_fee: Option<Uint128>,
+    pub registration_model: RegistrationModel,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -418,6 +933,90 @@ pub struct MintingFeesResponse {
     pub base_mint_fee: Option<Uint128>,
     pub burn_percentage: Option<u64>,
     pub short_name_surcharge: Option<SurchargeInfo>,
+    pub path_mint_fee: Option<Uint128>,
+    pub bonding_curve: Option<BondingCurve>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintPriceResponse {
+    pub native_denom: String,
+    /// The category the name matched, if any
+    pub category: Option<NameCategory>,
+    /// Whether the name can be minted
+    /// false if its category is not enabled
+    pub enabled: bool,
+    /// What must be sent with Mint
+    pub fee: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositResponse {
+    pub native_denom: String,
+    /// What will be returned to the owner when the token is burned
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TextRecordResponse {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TextRecordsResponse {
+    pub records: Vec<TextRecordResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NameCategoriesResponse {
+    pub categories: Vec<NameCategoryPricing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PathMintPriceResponse {
+    pub native_denom: String,
+    /// The fee set by the admin, if any
+    pub protocol_fee: Option<Uint128>,
+    /// The fee set by the owner of the base token, if any
+    /// this is not charged to the owner of the base token themselves
+    pub namespace_fee: Option<Uint128>,
+    /// What must be sent with MintPath
+    pub total: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintAllowanceResponse {
+    /// How many more mints the address can make in the current window
+    /// None if there is no limit
+    pub address_remaining: Option<u32>,
+    /// How many more mints anyone can make in the current block
+    /// None if there is no limit
+    pub block_remaining: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataFieldSource {
+    /// The name of the field, as it is serialized
+    pub field: String,
+    /// The token the value was taken from
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataRevisionResponse {
+    pub revision: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ResolvedMetadataResponse {
+    pub token_id: String,
+    /// Whether the token inherits metadata from its ancestors
+    pub inherit: bool,
+    /// The merged metadata
+    /// parent_token_id is always the token's own
+    pub metadata: Metadata,
+    /// Where each field that is set came from
+    pub sources: Vec<MetadataFieldSource>,
 }
 
 /// Is this a contract? Can it be executed?
@@ -445,6 +1044,43 @@ pub struct GetPathResponse {
     pub path: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ResolvePathResponse {
+    pub token_id: String,
+    pub owner: String,
+    pub contract_address: Option<String>,
+    pub metadata: Metadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TreeNode {
+    pub token_id: String,
+    pub owner: String,
+    pub metadata: Option<Metadata>,
+    pub children: Vec<TreeNode>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TreeResponse {
+    /// The tree rooted at the token that was asked for
+    /// on later pages, nodes above start_after are repeated
+    /// so the tree still starts from the same root
+    pub root: TreeNode,
+    /// Set if the limit was hit before the whole tree was returned
+    pub next_start_after: Option<String>,
+}
+
+/// The structure of a path token
+/// e.g. jeffvader::vehicles::tie-fighter has
+/// a root of jeffvader and segments of vehicles and tie-fighter
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PathInfo {
+    /// The base token the path sits under
+    pub root: String,
+    /// Each level of the path below the root, in order
+    pub segments: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct UserInfo {
     pub alias: String,
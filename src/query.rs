@@ -1,18 +1,43 @@
+use crate::bech32;
 use crate::msg::{
-    AddressOfResponse, ContractInfoResponse, GetParentIdResponse, GetPathResponse,
-    IsContractResponse, ListUserInfoResponse, PrimaryAliasResponse, UserInfo,
+    AddressOfResponse, AddressRecord, AddressSource, AddressesResponse, AllPrimaryAliasesResponse,
+    Chain, ContractInfoResponse, DepositResponse, DerivedAddress, DerivedAddressesResponse,
+    Extension, Fuses, GetParentIdResponse, GetPathResponse, IsContractResponse,
+    ListUserInfoResponse, Metadata, MetadataFieldSource, MetadataRevisionResponse,
+    MintAllowanceResponse, MintPriceResponse, NameCategoriesResponse, PathLimits,
+    PathMintPriceResponse, PrimaryAliasEntry, PrimaryAliasPolicy, PrimaryAliasRecord,
+    PrimaryAliasResponse, PrimaryAliasSource, PrimaryAliasesResponse, ProfileResponse, RateLimits,
+    RegistrationModel, ResolvePathResponse, ResolvedMetadataResponse, TextRecordLimits,
+    TextRecordResponse, TextRecordsResponse, TreeNode, TreeResponse, UserInfo,
     WhoamiNftInfoResponse,
 };
-use crate::state::{CONTRACT_INFO, MINTING_FEES_INFO, PRIMARY_ALIASES};
-use crate::utils::{is_path, namespace_in_path, remove_namespace_from_path};
+use crate::state::{
+    ADDRESS_RECORDS, ALIAS_INDEX, BASE_TOKEN_COUNT, CONTRACT_INFO, CONTRACT_REVERSE_RECORDS,
+    DEPOSITS, INHERIT_METADATA, MINTING_FEES_INFO, MINTS_BY_BLOCK, NAME_CATEGORIES, PARENT_INDEX,
+    PATH_LIMITS, PRIMARY_ALIASES, PRIMARY_ALIAS_POLICIES, RATE_LIMITS, REGISTRATION_MODEL,
+    TEXT_RECORDS, TEXT_RECORD_LIMITS,
+};
+use crate::utils::{
+    get_base_mint_fee, get_chain_key, get_fuses, get_metadata_revision, get_mints_in_window,
+    get_name_category, get_name_mint_fee, get_parent_of_path, get_path_info, get_path_mint_fees,
+    is_path, is_well_known_text_record_key, username_is_valid, well_known_text_records,
+};
 use crate::Cw721MetadataContract;
-use cosmwasm_std::{Deps, Env, Order, StdError, StdResult};
+use cosmwasm_std::{Addr, Deps, Env, Order, StdError, StdResult};
 use cw721::TokensResponse;
+use cw721_base::state::TokenInfo;
 use cw_storage_plus::Bound;
+use std::collections::BTreeSet;
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
 
+// enough for a page of an explorer in one query
+pub const MAX_ALIAS_BATCH: usize = 100;
+
+const DEFAULT_TREE_DEPTH: u32 = 3;
+const MAX_TREE_DEPTH: u32 = 10;
+
 fn get_tokens(
     contract: Cw721MetadataContract,
     deps: Deps,
@@ -84,24 +109,6 @@ pub fn get_base_tokens_for_owner(
     })
 }
 
-// get the first non_path token
-fn get_first_token_for_owner(
-    contract: Cw721MetadataContract,
-    deps: Deps,
-    owner: String,
-) -> StdResult<String> {
-    let tokens_response = get_base_tokens_for_owner(contract, deps, owner, None, Some(1))?;
-
-    if tokens_response.tokens.is_empty() {
-        Err(StdError::NotFound {
-            kind: "Primary alias not found".to_string(),
-        })
-    } else {
-        let first_token = &tokens_response.tokens[0];
-        Ok(first_token.to_string())
-    }
-}
-
 pub fn get_paths_for_owner(
     contract: Cw721MetadataContract,
     deps: Deps,
@@ -116,6 +123,205 @@ pub fn get_paths_for_owner(
     Ok(TokensResponse { tokens: paths })
 }
 
+// get the direct children of token_id from the parent index
+// this includes paths and any base tokens that
+// have token_id set as their parent
+pub fn get_children(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let tokens: Vec<String> = PARENT_INDEX
+        .prefix(&token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+// the first child directly under parent_token_id
+// that comes after start_after, if there is one
+fn get_next_child(
+    deps: Deps,
+    parent_token_id: &str,
+    start_after: Option<&str>,
+) -> StdResult<Option<String>> {
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    PARENT_INDEX
+        .prefix(parent_token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .next()
+        .transpose()
+}
+
+// the first path directly under parent_token_id
+// that comes after start_after, if there is one
+fn get_next_child_path(
+    deps: Deps,
+    parent_token_id: &str,
+    start_after: Option<&str>,
+) -> StdResult<Option<String>> {
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    PARENT_INDEX
+        .prefix(parent_token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .find(|child| match child {
+            Ok(child) => is_path(child),
+            Err(_) => true,
+        })
+        .transpose()
+}
+
+// walks every path nested under token_id, depth first
+// resuming after start_after if it is set
+// only paths for which include returns true are collected
+// and the walk stops once limit have been collected
+pub fn walk_paths<F>(
+    deps: Deps,
+    token_id: &str,
+    start_after: Option<String>,
+    limit: usize,
+    mut include: F,
+) -> StdResult<Vec<String>>
+where
+    F: FnMut(&str) -> StdResult<bool>,
+{
+    let mut paths = vec![];
+
+    // a cursor from outside this subtree
+    // can't be resumed from
+    if let Some(ref start_after) = start_after {
+        if !start_after.starts_with(&format!("{}::", token_id)) {
+            return Ok(paths);
+        }
+    }
+
+    // where we are in the tree
+    // and whether we should head down into its children next
+    let mut current = start_after.unwrap_or_else(|| token_id.to_string());
+    let mut descend = true;
+
+    while paths.len() < limit {
+        let next = if descend {
+            get_next_child_path(deps, &current, None)?
+        } else {
+            None
+        };
+
+        match next {
+            Some(child) => {
+                current = child;
+            }
+            None => {
+                // no children left, so head back up
+                // until there is a sibling we have not seen
+                if current == token_id {
+                    break;
+                }
+                let parent = match get_parent_of_path(&current) {
+                    Some(parent) => parent.to_string(),
+                    None => break,
+                };
+                match get_next_child_path(deps, &parent, Some(&current))? {
+                    Some(sibling) => {
+                        current = sibling;
+                    }
+                    None => {
+                        current = parent;
+                        descend = false;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        descend = true;
+        if include(&current)? {
+            paths.push(current.clone());
+        }
+    }
+
+    Ok(paths)
+}
+
+// walks the paths nested under token_id, depth first
+// but children always come before their parent
+// so any prefix of the result can be burned
+// without leaving paths that are unreachable from token_id
+// paths for which keep returns true are left out, along with everything under them
+// and so is everything above them, so that they are never orphaned
+pub fn walk_paths_leaves_first<F>(
+    deps: Deps,
+    token_id: &str,
+    limit: usize,
+    mut keep: F,
+) -> StdResult<Vec<String>>
+where
+    F: FnMut(&str) -> StdResult<bool>,
+{
+    let mut paths = vec![];
+    let mut kept_above: BTreeSet<String> = BTreeSet::new();
+    let mut current = token_id.to_string();
+
+    // the next child of parent that isn't being kept
+    let mut next_child =
+        |parent: &str, after: Option<&str>, kept_above: &mut BTreeSet<String>| -> StdResult<_> {
+            let mut after = after.map(String::from);
+            loop {
+                match get_next_child_path(deps, parent, after.as_deref())? {
+                    Some(child) if keep(&child)? => {
+                        let mut ancestor = get_parent_of_path(&child);
+                        while let Some(id) = ancestor {
+                            kept_above.insert(id.to_string());
+                            ancestor = get_parent_of_path(id);
+                        }
+                        after = Some(child);
+                    }
+                    next => return Ok(next),
+                }
+            }
+        };
+
+    loop {
+        // head down to the first leaf
+        while let Some(child) = next_child(&current, None, &mut kept_above)? {
+            current = child;
+        }
+
+        // then collect on the way back up
+        // until there is a sibling to head down into
+        loop {
+            if current == token_id || paths.len() >= limit {
+                return Ok(paths);
+            }
+            if !kept_above.contains(&current) {
+                paths.push(current.clone());
+            }
+
+            let parent = match get_parent_of_path(&current) {
+                Some(parent) => parent.to_string(),
+                None => return Ok(paths),
+            };
+            match next_child(&parent, Some(&current), &mut kept_above)? {
+                Some(sibling) => {
+                    current = sibling;
+                    break;
+                }
+                None => {
+                    current = parent;
+                }
+            }
+        }
+    }
+}
+
 // get only those namespaced under token_id
 pub fn get_paths_for_owner_and_token(
     deps: Deps,
@@ -125,12 +331,15 @@ pub fn get_paths_for_owner_and_token(
     limit: Option<u32>,
 ) -> StdResult<TokensResponse> {
     let contract = Cw721MetadataContract::default();
-    let tokens = get_tokens(contract, deps, owner, start_after, limit)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let owner_addr = deps.api.addr_validate(&owner)?;
 
-    let paths = tokens
-        .into_iter()
-        .filter(|path| is_path(path) && namespace_in_path(path, &token_id))
-        .collect();
+    // filter on owner as we go, so that limit
+    // applies to what is actually returned
+    let paths = walk_paths(deps, &token_id, start_after, limit, |path| {
+        let token = contract.tokens.load(deps.storage, path)?;
+        Ok(token.owner == owner_addr)
+    })?;
 
     Ok(TokensResponse { tokens: paths })
 }
@@ -143,19 +352,177 @@ pub fn primary_alias(
     owner: String,
 ) -> StdResult<PrimaryAliasResponse> {
     let owner_addr = deps.api.addr_validate(&owner)?;
-    let existing_alias = PRIMARY_ALIASES.may_load(deps.storage, &owner_addr)?;
-
-    // if nothing returned, get first
-    let username = match existing_alias {
-        Some(alias) => alias,
-        None => get_first_token_for_owner(contract, deps, owner)?,
-    };
+    let (username, _) =
+        get_primary_alias_and_source(&contract, deps, &owner_addr)?.ok_or_else(|| {
+            StdError::NotFound {
+                kind: "Primary alias not found".to_string(),
+            }
+        })?;
     Ok(PrimaryAliasResponse { username })
 }
 
+// the alias for an address, and where it came from
+// falls back to the first base token owned, skipping any paths,
+// unless the owner's policy says otherwise
+pub fn get_primary_alias_and_source(
+    contract: &Cw721MetadataContract,
+    deps: Deps,
+    owner_addr: &Addr,
+) -> StdResult<Option<(String, PrimaryAliasSource)>> {
+    if let Some(alias) = PRIMARY_ALIASES.may_load(deps.storage, owner_addr)? {
+        return Ok(Some((alias, PrimaryAliasSource::Explicit)));
+    }
+
+    // a contract's record only counts while the name still points at it
+    // and hasn't changed hands since the record was set
+    if let Some(record) = CONTRACT_REVERSE_RECORDS.may_load(deps.storage, owner_addr)? {
+        let token = contract.tokens.may_load(deps.storage, &record.token_id)?;
+        if let Some(token) = token {
+            if token.extension.contract_address.as_deref() == Some(owner_addr.as_str())
+                && token.owner == record.owner
+            {
+                return Ok(Some((record.token_id, PrimaryAliasSource::ContractRecord)));
+            }
+        }
+    }
+
+    // only fall back if the owner hasn't chosen otherwise
+    let policy = PRIMARY_ALIAS_POLICIES
+        .may_load(deps.storage, owner_addr)?
+        .unwrap_or_default();
+    if policy != PrimaryAliasPolicy::FirstToken {
+        return Ok(None);
+    }
+
+    let first_token = contract
+        .tokens
+        .idx
+        .owner
+        .prefix(owner_addr.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .find(|token_id| !matches!(token_id, Ok(id) if is_path(id)))
+        .transpose()?;
+    Ok(first_token.map(|token_id| (token_id, PrimaryAliasSource::FirstToken)))
+}
+
+// like primary_alias, but for many addresses
+// an address with no alias doesn't fail the rest
+pub fn primary_aliases(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    addresses: Vec<String>,
+) -> StdResult<PrimaryAliasesResponse> {
+    if addresses.len() > MAX_ALIAS_BATCH {
+        return Err(StdError::generic_err(format!(
+            "Too many addresses, at most {} can be looked up at once",
+            MAX_ALIAS_BATCH
+        )));
+    }
+
+    let aliases = addresses
+        .into_iter()
+        .map(|address| -> StdResult<PrimaryAliasEntry> {
+            let alias = match deps.api.addr_validate(&address) {
+                Ok(owner_addr) => get_primary_alias_and_source(&contract, deps, &owner_addr)?,
+                Err(_) => None,
+            };
+
+            let (username, source) = match alias {
+                Some((username, source)) => (Some(username), source),
+                None => (None, PrimaryAliasSource::None),
+            };
+
+            Ok(PrimaryAliasEntry {
+                address,
+                username,
+                source,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PrimaryAliasesResponse { aliases })
+}
+
+// everything a client needs to show for an address
+// in one call, rather than PrimaryAlias, then NftInfo, then GetFullPath
+pub fn profile(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    address: String,
+) -> StdResult<ProfileResponse> {
+    let owner_addr = deps.api.addr_validate(&address)?;
+    let (username, source) = get_primary_alias_and_source(&contract, deps, &owner_addr)?
+        .ok_or_else(|| StdError::NotFound {
+            kind: "Primary alias not found".to_string(),
+        })?;
+
+    let token = contract.tokens.load(deps.storage, &username)?;
+    let path = get_path(contract, deps, username.clone())?.path;
+
+    Ok(ProfileResponse {
+        username,
+        owner: token.owner.to_string(),
+        metadata: token.extension,
+        path,
+        source,
+    })
+}
+
+// every explicitly set primary alias, for syncing off chain
+pub fn all_primary_aliases(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllPrimaryAliasesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let aliases = PRIMARY_ALIASES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, username)| PrimaryAliasRecord {
+                address: address.to_string(),
+                username,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllPrimaryAliasesResponse { aliases })
+}
+
+// the addresses that chose token_id, from the alias index
+pub fn primary_alias_addresses(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AddressesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let addresses = ALIAS_INDEX
+        .prefix(&token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|address| address.map(|address| address.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AddressesResponse { addresses })
+}
+
+pub fn primary_alias_policy(deps: Deps, address: String) -> StdResult<PrimaryAliasPolicy> {
+    let owner_addr = deps.api.addr_validate(&address)?;
+    Ok(PRIMARY_ALIAS_POLICIES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default())
+}
+
 pub fn contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
     let contract_info = CONTRACT_INFO.load(deps.storage)?;
     let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+    let base_token_count = BASE_TOKEN_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let current_base_mint_fee = get_base_mint_fee(&minting_fees, base_token_count);
 
     let contract_info_response = ContractInfoResponse {
         name: contract_info.name,
@@ -166,10 +533,178 @@ pub fn contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
         base_mint_fee: minting_fees.base_mint_fee,
         burn_percentage: minting_fees.burn_percentage,
         short_name_surcharge: minting_fees.short_name_surcharge,
+        path_mint_fee: minting_fees.path_mint_fee,
+        bonding_curve: minting_fees.bonding_curve,
+        base_token_count,
+        current_base_mint_fee,
+        registration_model: REGISTRATION_MODEL
+            .may_load(deps.storage)?
+            .unwrap_or(RegistrationModel::Fee),
     };
     Ok(contract_info_response)
 }
 
+// what it costs to mint a base token
+pub fn mint_price(deps: Deps, token_id: String) -> StdResult<MintPriceResponse> {
+    // username == token_id
+    // normalize it to lowercase
+    let username = token_id.to_lowercase();
+    if !username_is_valid(deps, &username) {
+        return Err(StdError::generic_err("Token Name Invalid"));
+    }
+
+    let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+    let category = get_name_category(deps.storage, &username)?;
+    let base_token_count = BASE_TOKEN_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let fee = get_name_mint_fee(minting_fees.clone(), &username, &category, base_token_count);
+
+    Ok(MintPriceResponse {
+        native_denom: minting_fees.native_denom,
+        enabled: !matches!(category, Some(ref pricing) if !pricing.enabled),
+        category: category.map(|pricing| pricing.category),
+        fee,
+    })
+}
+
+pub fn name_categories(deps: Deps) -> StdResult<NameCategoriesResponse> {
+    let categories = NAME_CATEGORIES.may_load(deps.storage)?.unwrap_or_default();
+    Ok(NameCategoriesResponse { categories })
+}
+
+// what it costs to mint a path under parent_token_id
+pub fn path_mint_price(deps: Deps, parent_token_id: String) -> StdResult<PathMintPriceResponse> {
+    let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+    let (protocol_fee, namespace_fee) =
+        get_path_mint_fees(deps.storage, &minting_fees, &parent_token_id)?;
+    let total = protocol_fee.unwrap_or_default() + namespace_fee.unwrap_or_default();
+
+    Ok(PathMintPriceResponse {
+        native_denom: minting_fees.native_denom,
+        protocol_fee,
+        namespace_fee,
+        total,
+    })
+}
+
+pub fn path_limits(deps: Deps) -> StdResult<PathLimits> {
+    let path_limits = PATH_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(path_limits)
+}
+
+pub fn text_record_limits(deps: Deps) -> StdResult<TextRecordLimits> {
+    let text_record_limits = TEXT_RECORD_LIMITS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    Ok(text_record_limits)
+}
+
+pub fn text_record(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    key: String,
+) -> StdResult<TextRecordResponse> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    let value = if is_well_known_text_record_key(&key) {
+        well_known_text_records(&token.extension)
+            .into_iter()
+            .find(|(well_known_key, _)| *well_known_key == key)
+            .and_then(|(_, value)| value)
+    } else {
+        TEXT_RECORDS.may_load(deps.storage, (&token_id, &key))?
+    };
+
+    match value {
+        Some(value) => Ok(TextRecordResponse { key, value }),
+        None => Err(StdError::NotFound {
+            kind: "Text record not found".to_string(),
+        }),
+    }
+}
+
+// stored records merged with the well-known ones set in the metadata
+// they never share a key, so a merge by key is enough
+pub fn text_records(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TextRecordsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+
+    let mut well_known = well_known_text_records(&token.extension)
+        .into_iter()
+        .filter_map(|(key, value)| {
+            value.map(|value| TextRecordResponse {
+                key: key.to_string(),
+                value,
+            })
+        })
+        .filter(|record| !matches!(start_after, Some(ref start) if record.key <= *start))
+        .peekable();
+
+    let start = start_after.clone().map(|s| Bound::ExclusiveRaw(s.into()));
+    let mut stored = TEXT_RECORDS
+        .prefix(&token_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .map(|item| item.map(|(key, value)| TextRecordResponse { key, value }))
+        .peekable();
+
+    let mut records = vec![];
+    while records.len() < limit {
+        let take_well_known = match (well_known.peek(), stored.peek()) {
+            (Some(w), Some(Ok(s))) => w.key < s.key,
+            (Some(_), None) => true,
+            (None, None) => break,
+            _ => false,
+        };
+        if take_well_known {
+            records.extend(well_known.next());
+        } else if let Some(record) = stored.next() {
+            records.push(record?);
+        }
+    }
+
+    Ok(TextRecordsResponse { records })
+}
+
+pub fn rate_limits(deps: Deps) -> StdResult<RateLimits> {
+    let rate_limits = RATE_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(rate_limits)
+}
+
+pub fn mint_allowance(deps: Deps, env: Env, address: String) -> StdResult<MintAllowanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let rate_limits = RATE_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let height = env.block.height;
+
+    let address_remaining = match rate_limits.max_mints_per_address {
+        Some(max_mints_per_address) => {
+            let window_blocks = rate_limits.window_blocks.unwrap_or(1);
+            let mints = get_mints_in_window(deps.storage, &address, height, window_blocks)?;
+            Some(max_mints_per_address.saturating_sub(mints))
+        }
+        None => None,
+    };
+    let block_remaining = match rate_limits.max_mints_per_block {
+        Some(max_mints_per_block) => {
+            let mints = MINTS_BY_BLOCK
+                .may_load(deps.storage, height)?
+                .unwrap_or_default();
+            Some(max_mints_per_block.saturating_sub(mints))
+        }
+        None => None,
+    };
+
+    Ok(MintAllowanceResponse {
+        address_remaining,
+        block_remaining,
+    })
+}
+
 pub fn is_contract(
     contract: Cw721MetadataContract,
     deps: Deps,
@@ -197,10 +732,217 @@ pub fn address_of(
     token_id: String,
 ) -> StdResult<AddressOfResponse> {
     let token = contract.tokens.load(deps.storage, &token_id)?;
+    let (metadata, _) = get_resolved_metadata(&contract, deps, &token_id)?;
     Ok(AddressOfResponse {
         owner: token.owner.to_string(),
-        contract_address: token.extension.contract_address,
-        validator_address: token.extension.validator_operator_address,
+        contract_address: metadata.contract_address,
+        validator_address: metadata.validator_operator_address,
+    })
+}
+
+pub fn metadata_revision(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+) -> StdResult<MetadataRevisionResponse> {
+    // check the token exists
+    contract.tokens.load(deps.storage, &token_id)?;
+
+    let revision = get_metadata_revision(deps.storage, &token_id)?;
+    Ok(MetadataRevisionResponse { revision })
+}
+
+// an explicit address record for another chain
+pub fn address_of_on_chain(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    chain: Chain,
+) -> StdResult<AddressRecord> {
+    // check the token exists
+    contract.tokens.load(deps.storage, &token_id)?;
+
+    ADDRESS_RECORDS
+        .may_load(deps.storage, (&token_id, &get_chain_key(&chain)))?
+        .ok_or_else(|| StdError::NotFound {
+            kind: "Address record not found".to_string(),
+        })
+}
+
+// the owner's address on sibling cosmos chains
+// the same key gives the same address bytes, so only the prefix changes
+pub fn derived_addresses(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    prefixes: Vec<String>,
+) -> StdResult<DerivedAddressesResponse> {
+    if prefixes.len() > MAX_LIMIT as usize {
+        return Err(StdError::generic_err(format!(
+            "Too many prefixes, at most {} can be looked up at once",
+            MAX_LIMIT
+        )));
+    }
+
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    let owner_bytes = deps.api.addr_canonicalize(token.owner.as_str())?;
+
+    let addresses = prefixes
+        .into_iter()
+        .map(|prefix| -> StdResult<DerivedAddress> {
+            if !bech32::hrp_is_valid(&prefix) {
+                return Err(StdError::generic_err(format!(
+                    "Invalid bech32 prefix: {}",
+                    prefix
+                )));
+            }
+
+            let chain = Chain::Bech32 {
+                prefix: prefix.clone(),
+            };
+            let record =
+                ADDRESS_RECORDS.may_load(deps.storage, (&token_id, &get_chain_key(&chain)))?;
+            let (address, source) = match record {
+                Some(record) => (record.address, AddressSource::Explicit),
+                None => (
+                    bech32::encode(&prefix, owner_bytes.as_slice()),
+                    AddressSource::Derived,
+                ),
+            };
+
+            Ok(DerivedAddress {
+                prefix,
+                address,
+                source,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DerivedAddressesResponse { addresses })
+}
+
+// takes the value from a token if one hasn't been found already
+fn inherit_field<T: Clone>(
+    field: &mut Option<T>,
+    value: &Option<T>,
+    name: &str,
+    token_id: &str,
+    sources: &mut Vec<MetadataFieldSource>,
+) {
+    if field.is_none() && value.is_some() {
+        *field = value.clone();
+        sources.push(MetadataFieldSource {
+            field: name.to_string(),
+            token_id: token_id.to_string(),
+        });
+    }
+}
+
+// merges a token's metadata with its ancestors'
+// nearest first, if it inherits metadata
+// otherwise this is just the token's own metadata
+fn get_resolved_metadata(
+    contract: &Cw721MetadataContract,
+    deps: Deps,
+    token_id: &str,
+) -> StdResult<(Metadata, Vec<MetadataFieldSource>)> {
+    let token = contract.tokens.load(deps.storage, token_id)?;
+    let inherit = INHERIT_METADATA.has(deps.storage, token_id);
+
+    let mut metadata = Metadata {
+        parent_token_id: token.extension.parent_token_id.clone(),
+        ..Metadata::default()
+    };
+    let mut sources = vec![];
+
+    let mut current = Some((token_id.to_string(), token.extension));
+    while let Some((id, ext)) = current {
+        let m = &mut metadata;
+        let s = &mut sources;
+        inherit_field(&mut m.image, &ext.image, "image", &id, s);
+        inherit_field(&mut m.image_data, &ext.image_data, "image_data", &id, s);
+        inherit_field(&mut m.email, &ext.email, "email", &id, s);
+        inherit_field(
+            &mut m.external_url,
+            &ext.external_url,
+            "external_url",
+            &id,
+            s,
+        );
+        inherit_field(&mut m.public_name, &ext.public_name, "public_name", &id, s);
+        inherit_field(&mut m.public_bio, &ext.public_bio, "public_bio", &id, s);
+        inherit_field(&mut m.twitter_id, &ext.twitter_id, "twitter_id", &id, s);
+        inherit_field(&mut m.discord_id, &ext.discord_id, "discord_id", &id, s);
+        inherit_field(&mut m.telegram_id, &ext.telegram_id, "telegram_id", &id, s);
+        inherit_field(&mut m.keybase_id, &ext.keybase_id, "keybase_id", &id, s);
+        inherit_field(
+            &mut m.validator_operator_address,
+            &ext.validator_operator_address,
+            "validator_operator_address",
+            &id,
+            s,
+        );
+        inherit_field(
+            &mut m.contract_address,
+            &ext.contract_address,
+            "contract_address",
+            &id,
+            s,
+        );
+        inherit_field(
+            &mut m.pgp_public_key,
+            &ext.pgp_public_key,
+            "pgp_public_key",
+            &id,
+            s,
+        );
+
+        current = match ext.parent_token_id {
+            Some(parent_id) if inherit => {
+                let parent = contract.tokens.load(deps.storage, &parent_id)?;
+                Some((parent_id, parent.extension))
+            }
+            _ => None,
+        };
+    }
+
+    Ok((metadata, sources))
+}
+
+// errors if the token doesn't exist
+pub fn deposit(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+) -> StdResult<DepositResponse> {
+    contract.tokens.load(deps.storage, &token_id)?;
+    let minting_fees = MINTING_FEES_INFO.load(deps.storage)?;
+    let amount = DEPOSITS
+        .may_load(deps.storage, &token_id)?
+        .unwrap_or_default();
+    Ok(DepositResponse {
+        native_denom: minting_fees.native_denom,
+        amount,
+    })
+}
+
+// errors if the token doesn't exist
+pub fn fuses(contract: Cw721MetadataContract, deps: Deps, token_id: String) -> StdResult<Fuses> {
+    contract.tokens.load(deps.storage, &token_id)?;
+    get_fuses(deps.storage, &token_id)
+}
+
+pub fn resolved_metadata(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+) -> StdResult<ResolvedMetadataResponse> {
+    let (metadata, sources) = get_resolved_metadata(&contract, deps, &token_id)?;
+    Ok(ResolvedMetadataResponse {
+        inherit: INHERIT_METADATA.has(deps.storage, &token_id),
+        token_id,
+        metadata,
+        sources,
     })
 }
 
@@ -255,8 +997,8 @@ pub fn get_parent_nft_info(
 // get full path by heading up through the parents
 // paths for top level names would be jeffvader/anothertoken::some-nested-path
 // paths for nested paths would be like anothertoken::some-nested-path::second-nest
-// now in the case of that second-nest path, its parent is anothertoken::some-nested-path
-// essentially once you get into :: land you are into super fun recursive times
+// paths are stored with their root and segments, so only the chain
+// of base tokens above the root needs to be walked
 pub fn get_path(
     contract: Cw721MetadataContract,
     deps: Deps,
@@ -264,38 +1006,240 @@ pub fn get_path(
 ) -> StdResult<GetPathResponse> {
     let token = contract.tokens.load(deps.storage, &token_id)?;
 
-    // clip front off token_id if it is a path
-    let sanitized_token_id = match token.extension.parent_token_id {
-        Some(ref pti) => remove_namespace_from_path(&token_id, pti),
-        None => token_id,
-    };
-    let mut parents = vec![sanitized_token_id];
-    let mut current_parent_token_id = token.extension.parent_token_id;
+    // a token with no parent is its own full path
+    if token.extension.parent_token_id.is_none() {
+        return Ok(GetPathResponse { path: token_id });
+    }
+
+    let path_info = get_path_info(deps.storage, &token_id)?;
 
-    while current_parent_token_id.is_some() {
-        let cpti = current_parent_token_id.unwrap();
+    // walk up the base tokens from the root
+    let mut parents = vec![path_info.root.clone()];
+    let mut current_parent_token_id = contract
+        .tokens
+        .load(deps.storage, &path_info.root)?
+        .extension
+        .parent_token_id;
 
+    while let Some(cpti) = current_parent_token_id {
         // look up parent token
         let parent_token = contract.tokens.load(deps.storage, &cpti)?;
 
-        // clip off the front if this is a path
-        // i.e. jeffvader::employment will resolve
-        // so we clip off jeffvader
-        // not even sure this case _can_ happen, but still
-        let sanitized_parent_token_id = match parent_token.extension.parent_token_id {
-            Some(ref cppti) => remove_namespace_from_path(&cpti, cppti),
-            None => cpti,
-        };
-
         // insert current token
-        parents.insert(0, sanitized_parent_token_id);
+        parents.insert(0, cpti);
 
         // set the next one - this will be Some or None
         current_parent_token_id = parent_token.extension.parent_token_id;
     }
 
-    // finally, ensure we have no instances of /:: after join
-    let joined = parents.join("/");
-    let path = joined.replace("/::", "::");
+    // base tokens are joined by /
+    // and then the path segments by ::
+    let mut path = parents.join("/");
+    for segment in path_info.segments {
+        path.push_str("::");
+        path.push_str(&segment);
+    }
     Ok(GetPathResponse { path })
 }
+
+// load one link of a path
+// checking that it really sits under the link before it
+fn resolve_path_link(
+    contract: &Cw721MetadataContract,
+    deps: Deps,
+    token_id: &str,
+    parent_token_id: Option<&str>,
+) -> StdResult<TokenInfo<Extension>> {
+    let token = match contract.tokens.may_load(deps.storage, token_id)? {
+        Some(token) => token,
+        None => {
+            return Err(StdError::NotFound {
+                kind: format!("Path segment {}", token_id),
+            })
+        }
+    };
+
+    if token.extension.parent_token_id.as_deref() != parent_token_id {
+        let msg = match parent_token_id {
+            Some(parent) => format!("Path segment {} is not under {}", token_id, parent),
+            None => format!("Path segment {} is not a top level name", token_id),
+        };
+        return Err(StdError::generic_err(msg));
+    }
+
+    Ok(token)
+}
+
+// the reverse of get_path
+// base tokens are split by / and path segments by ::
+// so jeffvader/vehicles::tie-fighter is
+// jeffvader, then vehicles under it, then vehicles::tie-fighter under that
+pub fn resolve_path(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    path: String,
+) -> StdResult<ResolvePathResponse> {
+    let path = path.to_lowercase();
+
+    let mut parent_token_id: Option<String> = None;
+    let mut resolved: Option<(String, TokenInfo<Extension>)> = None;
+
+    for name in path.split('/') {
+        let mut segments = name.split("::");
+
+        // each name starts with a base token
+        let base_token_id = segments.next().unwrap_or_default().to_string();
+        let token = resolve_path_link(&contract, deps, &base_token_id, parent_token_id.as_deref())?;
+        let mut current = (base_token_id, token);
+
+        // then any path segments under it
+        for segment in segments {
+            let token_id = format!("{}::{}", current.0, segment);
+            let token = resolve_path_link(&contract, deps, &token_id, Some(&current.0))?;
+            current = (token_id, token);
+        }
+
+        parent_token_id = Some(current.0.clone());
+        resolved = Some(current);
+    }
+
+    match resolved {
+        Some((token_id, token)) => Ok(ResolvePathResponse {
+            token_id,
+            owner: token.owner.to_string(),
+            contract_address: token.extension.contract_address.clone(),
+            metadata: token.extension,
+        }),
+        None => Err(StdError::NotFound {
+            kind: "Path".to_string(),
+        }),
+    }
+}
+
+fn get_tree_node(
+    contract: &Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    include_metadata: bool,
+) -> StdResult<TreeNode> {
+    let token = contract.tokens.load(deps.storage, &token_id)?;
+    Ok(TreeNode {
+        token_id,
+        owner: token.owner.to_string(),
+        metadata: if include_metadata {
+            Some(token.extension)
+        } else {
+            None
+        },
+        children: vec![],
+    })
+}
+
+// everything nested under token_id, paths and base tokens alike
+// walked depth first through the parent index
+// and returned as a tree rooted at token_id
+// if start_after is set, the walk resumes after it
+// and its ancestors are repeated so the tree stays rooted at token_id
+pub fn get_tree(
+    contract: Cw721MetadataContract,
+    deps: Deps,
+    token_id: String,
+    max_depth: Option<u32>,
+    limit: Option<u32>,
+    start_after: Option<String>,
+    include_metadata: Option<bool>,
+) -> StdResult<TreeResponse> {
+    let max_depth = max_depth.unwrap_or(DEFAULT_TREE_DEPTH).min(MAX_TREE_DEPTH) as usize;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let include_metadata = include_metadata.unwrap_or(false);
+
+    // the chain from token_id down to where we are in the tree
+    let mut stack = vec![token_id.clone()];
+    if let Some(start_after) = start_after {
+        let mut chain = vec![];
+        let mut current = start_after;
+        while current != token_id {
+            let token = contract.tokens.load(deps.storage, &current)?;
+            match token.extension.parent_token_id {
+                Some(parent) => {
+                    chain.insert(0, current);
+                    current = parent;
+                }
+                None => {
+                    return Err(StdError::generic_err(format!(
+                        "{} is not under {}",
+                        current, token_id
+                    )))
+                }
+            }
+        }
+        stack.extend(chain);
+    }
+
+    // nodes in the order they are visited, with their depth
+    // this starts with the chain so that resumed pages still nest properly
+    let mut visited: Vec<(String, usize)> = stack
+        .iter()
+        .enumerate()
+        .map(|(depth, id)| (id.clone(), depth))
+        .collect();
+    let mut count = 0;
+    let mut next_start_after = None;
+    let mut descend = true;
+
+    loop {
+        let depth = stack.len() - 1;
+        let next = if descend && depth < max_depth {
+            get_next_child(deps, &stack[depth], None)?
+        } else {
+            None
+        };
+
+        let next = match next {
+            Some(child) => Some(child),
+            None => {
+                // head back up, looking for a sibling we have not seen
+                if stack.len() == 1 {
+                    break;
+                }
+                let finished = stack.pop().unwrap();
+                get_next_child(deps, &stack[stack.len() - 1], Some(&finished))?
+            }
+        };
+
+        match next {
+            Some(node) => {
+                if count == limit {
+                    next_start_after = visited.last().map(|(id, _)| id.clone());
+                    break;
+                }
+                stack.push(node.clone());
+                visited.push((node, stack.len() - 1));
+                count += 1;
+                descend = true;
+            }
+            None => {
+                descend = false;
+            }
+        }
+    }
+
+    // turn the visited nodes back into a tree
+    let mut nodes: Vec<TreeNode> = vec![];
+    for (id, depth) in visited {
+        while nodes.len() > depth {
+            let node = nodes.pop().unwrap();
+            nodes.last_mut().unwrap().children.push(node);
+        }
+        nodes.push(get_tree_node(&contract, deps, id, include_metadata)?);
+    }
+    while nodes.len() > 1 {
+        let node = nodes.pop().unwrap();
+        nodes.last_mut().unwrap().children.push(node);
+    }
+
+    Ok(TreeResponse {
+        root: nodes.pop().unwrap(),
+        next_start_after,
+    })
+}
@@ -1,10 +1,28 @@
-use crate::msg::{ContractInfo, MintingFeesResponse};
-use cosmwasm_std::Addr;
+use crate::msg::{
+    AddressRecord, ContractInfo, Fuses, MintingFeesResponse, NameCategoryPricing, PathInfo,
+    PathLimits, PrimaryAliasPolicy, RateLimits, RegistrationModel, TextRecordLimits,
+};
+use cosmwasm_std::{Addr, Empty, Uint128};
 use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
 
 // this is a mapping of address to token_id
 pub const PRIMARY_ALIASES: Map<&Addr, String> = Map::new("aliases");
 
+// reverse of PRIMARY_ALIASES, token_id -> addresses that chose it
+// kept in step by set_primary_alias and remove_primary_alias
+pub const ALIAS_INDEX: Map<(&str, &Addr), Empty> = Map::new("alias_index");
+
+// what each address wants to happen when its primary alias leaves the account
+// an address with no entry falls back to the first token owned
+pub const PRIMARY_ALIAS_POLICIES: Map<&Addr, PrimaryAliasPolicy> = Map::new("alias_policies");
+
+// mapping of contract address to token_id, set by the contract's admin
+// only used while the token's contract_address still points at the contract
+// and the token is still held by whoever owned it when the record was set
+pub const CONTRACT_REVERSE_RECORDS: Map<&Addr, ContractReverseRecord> =
+    Map::new("contract_reverse_records");
+
 // this is the legacy contract info
 // you should no longer write to it
 pub const LEGACY_CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
@@ -17,3 +35,110 @@ pub const USERNAME_LENGTH_CAP: Item<u32> = Item::new("username_length_cap");
 
 // this is fees info
 pub const MINTING_FEES_INFO: Item<MintingFeesResponse> = Item::new("minting_fees");
+
+// these are the name categories priced separately, in order of precedence
+// if unset, every name is priced by length alone
+pub const NAME_CATEGORIES: Item<Vec<NameCategoryPricing>> = Item::new("name_categories");
+
+// this is a secondary index of (parent_token_id, token_id)
+// it mirrors the parent_token_id field in token metadata
+// so children can be found without scanning an owner's tokens
+pub const PARENT_INDEX: Map<(&str, &str), Empty> = Map::new("parent_index");
+
+// tokens whose paths are still being burned
+// these are locked, along with everything under them, until ContinueBurnPaths clears the rest
+// if an owner is set, only the paths it owns are being burned
+pub const PENDING_PATH_BURNS: Map<&str, Option<Addr>> = Map::new("pending_path_burns");
+
+// this is the structure of each path token, keyed by token_id
+// base tokens have no entry
+pub const PATHS: Map<&str, PathInfo> = Map::new("paths");
+
+// these are the limits on paths set by the admin
+// if unset, there are no limits
+pub const PATH_LIMITS: Item<PathLimits> = Item::new("path_limits");
+
+// this is how many paths sit under each base token
+pub const PATH_COUNTS: Map<&str, u32> = Map::new("path_counts");
+
+// this is the fee for minting paths under a base token
+// set by, and paid to, the owner of the base token
+pub const NAMESPACE_FEES: Map<&str, Uint128> = Map::new("namespace_fees");
+
+// tokens that fall back to their ancestors' metadata
+// for any field they leave unset
+pub const INHERIT_METADATA: Map<&str, Empty> = Map::new("inherit_metadata");
+
+// the permissions burned on each token
+// these can never be unburned
+pub const FUSES: Map<&str, Fuses> = Map::new("fuses");
+
+// these are the limits on how quickly tokens can be minted
+// if unset, there are no limits
+pub const RATE_LIMITS: Item<RateLimits> = Item::new("rate_limits");
+
+// how many mints each address made at each height
+// anything older than the window is pruned as new mints come in
+pub const MINTS_BY_ADDRESS: Map<(&Addr, u64), u32> = Map::new("mints_by_address");
+
+// how many mints were made at each height
+// only the current height is kept
+pub const MINTS_BY_BLOCK: Map<u64, u32> = Map::new("mints_by_block");
+
+// this is how many base tokens are registered
+// paths are not counted
+pub const BASE_TOKEN_COUNT: Item<u64> = Item::new("base_token_count");
+
+// how mint fees are handled, set at instantiation
+// if unset, fees are paid out
+pub const REGISTRATION_MODEL: Item<RegistrationModel> = Item::new("registration_model");
+
+// the deposit held for each token in the deposit model
+// returned to whoever owns the token when it is burned
+pub const DEPOSITS: Map<&str, Uint128> = Map::new("deposits");
+
+// limits on text records, set by the admin
+// if unset, there are no limits
+pub const TEXT_RECORD_LIMITS: Item<TextRecordLimits> = Item::new("text_record_limits");
+
+// (token_id, key) -> value
+// the well-known keys are read from the metadata instead
+pub const TEXT_RECORDS: Map<(&str, &str), String> = Map::new("text_records");
+
+// (token_id, chain key) -> address on that chain
+// see get_chain_key for how the chain is keyed
+pub const ADDRESS_RECORDS: Map<(&str, &str), AddressRecord> = Map::new("address_records");
+
+// bumped every time a token's metadata changes
+// so that concurrent patches can be detected
+// a token with no entry is at revision 0
+pub const METADATA_REVISIONS: Map<&str, u64> = Map::new("metadata_revisions");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContractReverseRecord {
+    pub token_id: String,
+    /// the owner of the token when the record was set
+    pub owner: Addr,
+}
+
+// what MigrateBatch does next
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStep {
+    ClearPathCounts,
+    Tokens,
+    Aliases,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrationProgress {
+    pub step: MigrationStep,
+    /// the last key handled in this step
+    pub start_after: Option<String>,
+    /// base tokens seen so far, saved once every token has been seen
+    pub base_token_count: u64,
+}
+
+// set by migrate, and removed once MigrateBatch has backfilled everything
+// every other execute message is rejected while it is set
+pub const MIGRATION_PROGRESS: Item<MigrationProgress> = Item::new("migration_progress");
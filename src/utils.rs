@@ -1,28 +1,28 @@
+use crate::bech32;
 use crate::error::ContractError;
-use crate::msg::MintingFeesResponse;
-use crate::state::USERNAME_LENGTH_CAP;
+use crate::execute::{MAX_TEXT_RECORDS, MAX_TEXT_RECORD_KEY_LENGTH, MAX_TEXT_RECORD_VALUE_LENGTH};
+use crate::msg::{
+    Chain, FieldUpdate, Fuses, Metadata, MintingFeesResponse, NameCategory, NameCategoryPricing,
+    PathInfo, RegistrationModel,
+};
+use crate::state::{
+    ADDRESS_RECORDS, ALIAS_INDEX, FUSES, METADATA_REVISIONS, MINTS_BY_ADDRESS, MINTS_BY_BLOCK,
+    NAMESPACE_FEES, NAME_CATEGORIES, PARENT_INDEX, PATHS, PATH_COUNTS, PATH_LIMITS,
+    PENDING_PATH_BURNS, PRIMARY_ALIASES, RATE_LIMITS, REGISTRATION_MODEL, TEXT_RECORDS,
+    TEXT_RECORD_LIMITS, USERNAME_LENGTH_CAP,
+};
 use cosmwasm_std::{
-    coins, Addr, BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Order, Response, StdError, StdResult,
-    Uint128,
+    coins, Addr, BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Empty, Order, Response, StdResult,
+    Storage, Uint128,
 };
-use cw20::{EmbeddedLogo, Logo};
+use cw_storage_plus::{Bound, Map};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::Cw721MetadataContract;
 use regex::Regex;
 use std::convert::TryFrom;
 
-// dumb, granted
-// but a basic sense check
-// that clients don't submit something random
-// obviously if the key is the correct format
-// but still in some way incorrect it will error later
-pub fn pgp_pubkey_format_is_valid(pgp_pubkey: &str) -> bool {
-    let first_check_passed = str::starts_with(pgp_pubkey, "-----BEGIN PGP PUBLIC KEY BLOCK-----");
-    let second_check_passed = str::ends_with(pgp_pubkey, "-----END PGP PUBLIC KEY BLOCK-----");
-
-    first_check_passed && second_check_passed
-}
-
 // for a subdomain, we need to validate:
 // first, is the parent_token_id an actual token?
 // if it's not, throw an error
@@ -81,7 +81,10 @@ pub fn username_is_valid(deps: Deps, username: &str) -> bool {
 // initially we allowed paths like employment/death-star-1
 // but it makes more sense to use the fact we use :: to namespace paths
 // to always resolve the parent
-pub fn validate_path_characters(path: &str, parent_token_id: &str) -> bool {
+// this validates a single segment of a path
+// paths are stored as segments, so the parent
+// appearing in the segment is no longer a problem
+pub fn validate_path_characters(path: &str) -> bool {
     // first check for any characters _other than_ allowed characters
     let invalid_characters: Regex = Regex::new(r"[^a-z0-9_\-]").unwrap();
     let first_check_passed = !invalid_characters.is_match(path);
@@ -91,27 +94,19 @@ pub fn validate_path_characters(path: &str, parent_token_id: &str) -> bool {
     let invalid_hyphens_underscores: Regex = Regex::new(r"[_\-/]{2,}").unwrap();
     let second_check_passed = !invalid_hyphens_underscores.is_match(path);
 
-    // check parent token isn't in there
-    let parent_token_id_present: Regex = Regex::new(parent_token_id).unwrap();
-    let third_check_passed = !parent_token_id_present.is_match(path);
-
     let leading_special_chars: Regex = Regex::new(r"^[_\-]").unwrap();
-    let fourth_check_passed = !leading_special_chars.is_match(path);
+    let third_check_passed = !leading_special_chars.is_match(path);
 
     let trailing_special_chars: Regex = Regex::new(r"[_\-]$").unwrap();
-    let fifth_check_passed = !trailing_special_chars.is_match(path);
+    let fourth_check_passed = !trailing_special_chars.is_match(path);
 
-    first_check_passed
-        && second_check_passed
-        && third_check_passed
-        && fourth_check_passed
-        && fifth_check_passed
+    first_check_passed && second_check_passed && third_check_passed && fourth_check_passed
 }
 
-pub fn path_is_valid(path: &str, parent_token_id: &str) -> bool {
+pub fn path_is_valid(path: &str) -> bool {
     let path_length = u32::try_from(path.chars().count()).unwrap();
     let path_length_valid = path_length <= 2048;
-    let path_characters_valid = validate_path_characters(path, parent_token_id);
+    let path_characters_valid = validate_path_characters(path);
     path_characters_valid && path_length_valid
 }
 
@@ -119,35 +114,478 @@ pub fn is_path(token_id: &str) -> bool {
     token_id.contains("::")
 }
 
-// check whether the offered token id matches the namespace
-// this specifically checks if it is at the beginning of the string
-// however note we actually do not allow the parent id anywhere in
-// the substring thanks to the validator fn - this prevents cycles
+// splits a token id into its root and segments
+// e.g. jeffvader::vehicles::tie-fighter is
+// jeffvader, with segments vehicles and tie-fighter
+// a base token is just a root with no segments
+pub fn parse_path(token_id: &str) -> PathInfo {
+    let mut parts = token_id.split("::").map(String::from);
+    PathInfo {
+        root: parts.next().unwrap_or_default(),
+        segments: parts.collect(),
+    }
+}
+
+// the token id for a path, i.e. root::segment::segment
+pub fn path_to_token_id(path: &PathInfo) -> String {
+    let mut parts = vec![path.root.as_str()];
+    parts.extend(path.segments.iter().map(String::as_str));
+    parts.join("::")
+}
+
+// the stored structure for a path
+// base tokens are not stored, so they come back as a root with no segments
+pub fn get_path_info(storage: &dyn Storage, token_id: &str) -> StdResult<PathInfo> {
+    match PATHS.may_load(storage, token_id)? {
+        Some(path) => Ok(path),
+        None => Ok(PathInfo {
+            root: token_id.to_string(),
+            segments: vec![],
+        }),
+    }
+}
+
+// check whether the offered token id sits under the namespace
+// this compares root and segments one by one
+// so the namespace is never treated as a pattern
 pub fn namespace_in_path(token_id: &str, parent_token_id: &str) -> bool {
     // okay so this is meant to identify a namespace
     // as part of a longer path
-    // as such there must be _at least_ one more character
-    // after the namespace. moreover, it _should be a separator_, '::'
-    let namespace_regex = format!("^{}::", parent_token_id);
-    let has_namespace: Regex = Regex::new(&namespace_regex).unwrap();
-    has_namespace.is_match(token_id)
+    // as such there must be _at least_ one more segment
+    // after the namespace
+    let path = parse_path(token_id);
+    let namespace = parse_path(parent_token_id);
+
+    path.root == namespace.root
+        && path.segments.len() > namespace.segments.len()
+        && path.segments.starts_with(&namespace.segments)
+}
+
+// a path is always namespaced under its parent
+// so the parent is everything before the last separator
+// returns None if this is not a path
+pub fn get_parent_of_path(path: &str) -> Option<&str> {
+    path.rsplit_once("::").map(|(parent, _)| parent)
+}
+
+// add a token to the parent index, if it has a parent
+// paths are also counted against their namespace
+pub fn add_to_parent_index(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    parent_token_id: &Option<String>,
+) -> StdResult<()> {
+    if let Some(parent) = parent_token_id {
+        PARENT_INDEX.save(storage, (parent, token_id), &Empty {})?;
+        if is_path(token_id) {
+            PATH_COUNTS.update(
+                storage,
+                get_root_of_path(token_id),
+                |count| -> StdResult<_> { Ok(count.unwrap_or_default() + 1) },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// remove a token from the parent index, if it has a parent
+pub fn remove_from_parent_index(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    parent_token_id: &Option<String>,
+) -> StdResult<()> {
+    if let Some(parent) = parent_token_id {
+        PARENT_INDEX.remove(storage, (parent, token_id));
+        if is_path(token_id) {
+            PATH_COUNTS.update(
+                storage,
+                get_root_of_path(token_id),
+                |count| -> StdResult<_> { Ok(count.unwrap_or_default().saturating_sub(1)) },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// sets an address's primary alias, keeping the alias index in step
+pub fn set_primary_alias(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    token_id: &str,
+) -> StdResult<()> {
+    remove_primary_alias(storage, address)?;
+    PRIMARY_ALIASES.save(storage, address, &token_id.to_string())?;
+    ALIAS_INDEX.save(storage, (token_id, address), &Empty {})
+}
+
+pub fn remove_primary_alias(storage: &mut dyn Storage, address: &Addr) -> StdResult<()> {
+    if let Some(alias) = PRIMARY_ALIASES.may_load(storage, address)? {
+        ALIAS_INDEX.remove(storage, (&alias, address));
+        PRIMARY_ALIASES.remove(storage, address);
+    }
+    Ok(())
+}
+
+pub fn get_metadata_revision(storage: &dyn Storage, token_id: &str) -> StdResult<u64> {
+    Ok(METADATA_REVISIONS
+        .may_load(storage, token_id)?
+        .unwrap_or_default())
+}
+
+// call whenever a token's metadata is written
+pub fn bump_metadata_revision(storage: &mut dyn Storage, token_id: &str) -> StdResult<u64> {
+    let revision = get_metadata_revision(storage, token_id)? + 1;
+    METADATA_REVISIONS.save(storage, token_id, &revision)?;
+    Ok(revision)
+}
+
+pub fn apply_field_update<T>(field: &mut Option<T>, update: Option<FieldUpdate<T>>) {
+    match update {
+        Some(FieldUpdate::Set(value)) => *field = Some(value),
+        Some(FieldUpdate::Clear) => *field = None,
+        None => {}
+    }
+}
+
+// text record keys that are read from the metadata
+// in key order, so they can be merged with stored records
+pub fn well_known_text_records(metadata: &Metadata) -> Vec<(&'static str, Option<String>)> {
+    vec![
+        ("avatar", metadata.image.clone()),
+        ("com.discord", metadata.discord_id.clone()),
+        ("com.twitter", metadata.twitter_id.clone()),
+        ("description", metadata.public_bio.clone()),
+        ("email", metadata.email.clone()),
+        ("io.keybase", metadata.keybase_id.clone()),
+        ("name", metadata.public_name.clone()),
+        ("org.telegram", metadata.telegram_id.clone()),
+        ("url", metadata.external_url.clone()),
+    ]
+}
+
+pub fn is_well_known_text_record_key(key: &str) -> bool {
+    well_known_text_records(&Metadata::default())
+        .iter()
+        .any(|(well_known_key, _)| *well_known_key == key)
+}
+
+// checks a text record against the limits set by the admin
+// new_record is whether the key is not already set
+pub fn validate_text_record(
+    storage: &dyn Storage,
+    token_id: &str,
+    key: &str,
+    value: &str,
+    new_record: bool,
+) -> Result<(), ContractError> {
+    let valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'));
+    if !valid_key {
+        return Err(ContractError::InvalidTextRecordKey {});
+    }
+    if is_well_known_text_record_key(key) {
+        return Err(ContractError::ReservedTextRecordKey {});
+    }
+
+    let limits = TEXT_RECORD_LIMITS.may_load(storage)?.unwrap_or_default();
+
+    // anything the admin hasn't set falls back to the default cap
+    let max_key_length = limits.max_key_length.unwrap_or(MAX_TEXT_RECORD_KEY_LENGTH);
+    if key.chars().count() > max_key_length as usize {
+        return Err(ContractError::TextRecordKeyTooLong {});
+    }
+
+    let max_value_length = limits
+        .max_value_length
+        .unwrap_or(MAX_TEXT_RECORD_VALUE_LENGTH);
+    if value.chars().count() > max_value_length as usize {
+        return Err(ContractError::TextRecordValueTooLong {});
+    }
+
+    let max_records = limits.max_records.unwrap_or(MAX_TEXT_RECORDS);
+    if new_record {
+        let count = TEXT_RECORDS
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Ascending)
+            .take(max_records as usize)
+            .count();
+        if count >= max_records as usize {
+            return Err(ContractError::TextRecordLimitReached {});
+        }
+    }
+
+    Ok(())
 }
 
-// if it is a path, removes the namespace
-// otherwise leaves it untouched
-pub fn remove_namespace_from_path(path: &str, parent_token_id: &str) -> String {
-    let parent_id_regex = Regex::new(parent_token_id).unwrap();
-    parent_id_regex.replace_all(path, "").to_string()
+fn move_token_records<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    records_map: Map<(&str, &str), T>,
+    token_id: &str,
+    new_token_id: Option<&str>,
+) -> StdResult<()> {
+    let records = records_map
+        .prefix(token_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, value) in records {
+        records_map.remove(storage, (token_id, &key));
+        if let Some(new_token_id) = new_token_id {
+            records_map.save(storage, (new_token_id, &key), &value)?;
+        }
+    }
+    Ok(())
+}
+
+// moves every text and address record from one token to another
+// or just drops them if there is nowhere to move them to
+pub fn move_records(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    new_token_id: Option<&str>,
+) -> StdResult<()> {
+    move_token_records(storage, TEXT_RECORDS, token_id, new_token_id)?;
+    move_token_records(storage, ADDRESS_RECORDS, token_id, new_token_id)
 }
 
-pub fn get_mint_fee(minting_fees: MintingFeesResponse, username_length: u32) -> Option<Uint128> {
+// how a chain is keyed in ADDRESS_RECORDS
+pub fn get_chain_key(chain: &Chain) -> String {
+    match chain {
+        Chain::Bech32 { prefix } => format!("bech32:{}", prefix),
+        Chain::Slip44 { coin_type } => format!("slip44:{}", coin_type),
+        Chain::Evm { chain_id } => format!("evm:{}", chain_id),
+    }
+}
+
+// 0x followed by 20 bytes of hex
+// the EIP-55 checksum casing is not checked
+fn evm_address_is_valid(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn bech32_address_is_valid(address: &str, prefix: &str) -> bool {
+    matches!(bech32::decode(address), Some((hrp, _)) if hrp == prefix)
+}
+
+// checks an address is in the format used by the chain
+// coin types we don't know the format of only get a basic check
+pub fn validate_address_record(chain: &Chain, address: &str) -> Result<(), ContractError> {
+    let valid = match chain {
+        Chain::Bech32 { prefix } => {
+            if !bech32::hrp_is_valid(prefix) {
+                return Err(ContractError::InvalidBech32Prefix {});
+            }
+            bech32_address_is_valid(address, prefix)
+        }
+        Chain::Evm { .. } | Chain::Slip44 { coin_type: 60 } => evm_address_is_valid(address),
+        Chain::Slip44 { coin_type: 118 } => bech32_address_is_valid(address, "cosmos"),
+        Chain::Slip44 { .. } => {
+            !address.is_empty()
+                && address.len() <= 128
+                && address.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+    };
+
+    if !valid {
+        return Err(ContractError::InvalidChainAddress {});
+    }
+    Ok(())
+}
+
+// checks a path against the limits set by the admin
+// depth is how many segments the path has below its root
+// new_paths is how many paths are being added to the namespace
+pub fn validate_path_limits(
+    storage: &dyn Storage,
+    root: &str,
+    depth: usize,
+    segment: &str,
+    new_paths: u32,
+) -> Result<(), ContractError> {
+    let path_limits = PATH_LIMITS.may_load(storage)?.unwrap_or_default();
+
+    if let Some(max_segment_length) = path_limits.max_segment_length {
+        if get_username_length(segment) > max_segment_length {
+            return Err(ContractError::PathSegmentTooLong {});
+        }
+    }
+
+    if let Some(max_depth) = path_limits.max_depth {
+        if depth > max_depth as usize {
+            return Err(ContractError::PathTooDeep {});
+        }
+    }
+
+    if let Some(max_paths) = path_limits.max_paths_per_namespace {
+        let count = PATH_COUNTS.may_load(storage, root)?.unwrap_or_default();
+        if count + new_paths > max_paths {
+            return Err(ContractError::NamespacePathLimitReached {});
+        }
+    }
+
+    Ok(())
+}
+
+// the base token at the top of a path
+// or the token itself if it is not a path
+pub fn get_root_of_path(path: &str) -> &str {
+    path.split("::").next().unwrap_or(path)
+}
+
+// a token is locked while paths under it are still being burned
+pub fn ensure_no_pending_path_burn(deps: Deps, token_id: &str) -> Result<(), ContractError> {
+    // a path is locked too if anything above it is still being burned
+    // as it is about to be burned along with it
+    let mut current = Some(token_id);
+    while let Some(id) = current {
+        if PENDING_PATH_BURNS.has(deps.storage, id) {
+            return Err(ContractError::PathBurnPending {});
+        }
+        current = get_parent_of_path(id);
+    }
+    Ok(())
+}
+
+// the fuses burned on a token
+// a token with no entry has none burned
+pub fn get_fuses(storage: &dyn Storage, token_id: &str) -> StdResult<Fuses> {
+    Ok(FUSES.may_load(storage, token_id)?.unwrap_or_default())
+}
+
+pub fn ensure_fuse_not_burned(
+    deps: Deps,
+    token_id: &str,
+    fuse: fn(&Fuses) -> bool,
+) -> Result<(), ContractError> {
+    if fuse(&get_fuses(deps.storage, token_id)?) {
+        return Err(ContractError::FuseBurned {});
+    }
+    Ok(())
+}
+
+// how many mints an address has made in the window ending at height
+pub fn get_mints_in_window(
+    storage: &dyn Storage,
+    address: &Addr,
+    height: u64,
+    window_blocks: u64,
+) -> StdResult<u32> {
+    let window_start = height.saturating_sub(window_blocks.max(1) - 1);
+    MINTS_BY_ADDRESS
+        .prefix(address)
+        .range(
+            storage,
+            Some(Bound::inclusive(window_start)),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, count)| count))
+        .sum()
+}
+
+// checks a mint against the rate limits set by the admin
+// and records it if it is allowed
+pub fn record_mint(
+    storage: &mut dyn Storage,
+    height: u64,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let rate_limits = RATE_LIMITS.may_load(storage)?.unwrap_or_default();
+
+    let block_mints = MINTS_BY_BLOCK
+        .may_load(storage, height)?
+        .unwrap_or_default();
+    if let Some(max_mints_per_block) = rate_limits.max_mints_per_block {
+        if block_mints >= max_mints_per_block {
+            return Err(ContractError::BlockMintLimitReached {});
+        }
+    }
+
+    let window_blocks = rate_limits.window_blocks.unwrap_or(1).max(1);
+    if let Some(max_mints_per_address) = rate_limits.max_mints_per_address {
+        if get_mints_in_window(storage, address, height, window_blocks)? >= max_mints_per_address {
+            return Err(ContractError::AddressMintLimitReached {});
+        }
+    }
+
+    // prune anything that has fallen out of the window
+    let window_start = height.saturating_sub(window_blocks - 1);
+    let stale_heights = MINTS_BY_ADDRESS
+        .prefix(address)
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(window_start)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for stale_height in stale_heights {
+        MINTS_BY_ADDRESS.remove(storage, (address, stale_height));
+    }
+    let stale_blocks = MINTS_BY_BLOCK
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(height)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for stale_block in stale_blocks {
+        MINTS_BY_BLOCK.remove(storage, stale_block);
+    }
+
+    MINTS_BY_ADDRESS.update(storage, (address, height), |count| -> StdResult<_> {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+    MINTS_BY_BLOCK.save(storage, height, &(block_mints + 1))?;
+    Ok(())
+}
+
+pub fn is_deposit_model(storage: &dyn Storage) -> StdResult<bool> {
+    let registration_model = REGISTRATION_MODEL.may_load(storage)?;
+    Ok(registration_model == Some(RegistrationModel::Deposit))
+}
+
+// the base mint fee, raised by any bonding curve
+// for the number of base tokens already registered
+pub fn get_base_mint_fee(
+    minting_fees: &MintingFeesResponse,
+    base_token_count: u64,
+) -> Option<Uint128> {
+    match minting_fees.bonding_curve {
+        Some(ref curve) => {
+            let steps = base_token_count / curve.base_names_per_step.max(1);
+            let increase = curve
+                .fee_increase_per_step
+                .saturating_mul(Uint128::from(steps));
+            let mut fee = minting_fees
+                .base_mint_fee
+                .unwrap_or_default()
+                .saturating_add(increase);
+            if let Some(max_base_mint_fee) = curve.max_base_mint_fee {
+                fee = fee.min(max_base_mint_fee);
+            }
+            Some(fee).filter(|fee| !fee.is_zero())
+        }
+        None => minting_fees.base_mint_fee,
+    }
+}
+
+pub fn get_mint_fee(
+    minting_fees: MintingFeesResponse,
+    username_length: u32,
+    base_token_count: u64,
+) -> Option<Uint128> {
     // is token name short enough to trigger a surcharge?
     let surcharge_is_owed = match minting_fees.short_name_surcharge {
         Some(ref sc) => username_length < sc.surcharge_max_characters,
         None => false,
     };
 
-    match minting_fees.base_mint_fee {
+    match get_base_mint_fee(&minting_fees, base_token_count) {
         Some(base_fee) => match minting_fees.short_name_surcharge {
             Some(sc) => {
                 if surcharge_is_owed {
@@ -173,6 +611,74 @@ pub fn get_mint_fee(minting_fees: MintingFeesResponse, username_length: u32) ->
     }
 }
 
+pub fn is_numeric_name(username: &str) -> bool {
+    !username.is_empty() && username.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn is_palindrome_name(username: &str) -> bool {
+    get_username_length(username) > 1 && username.chars().eq(username.chars().rev())
+}
+
+pub fn is_repeated_character_name(username: &str) -> bool {
+    let mut chars = username.chars();
+    match chars.next() {
+        Some(first) => get_username_length(username) > 1 && chars.all(|c| c == first),
+        None => false,
+    }
+}
+
+pub fn name_is_in_category(username: &str, category: &NameCategory) -> bool {
+    match category {
+        NameCategory::Numeric => is_numeric_name(username),
+        NameCategory::Palindrome => is_palindrome_name(username),
+        NameCategory::RepeatedCharacter => is_repeated_character_name(username),
+    }
+}
+
+// the first category set by the admin that the name matches
+pub fn get_name_category(
+    storage: &dyn Storage,
+    username: &str,
+) -> StdResult<Option<NameCategoryPricing>> {
+    let categories = NAME_CATEGORIES.may_load(storage)?.unwrap_or_default();
+    Ok(categories
+        .into_iter()
+        .find(|pricing| name_is_in_category(username, &pricing.category)))
+}
+
+// the mint fee for a name, with its category's multiplier applied
+pub fn get_name_mint_fee(
+    minting_fees: MintingFeesResponse,
+    username: &str,
+    category: &Option<NameCategoryPricing>,
+    base_token_count: u64,
+) -> Option<Uint128> {
+    let fee = get_mint_fee(
+        minting_fees,
+        get_username_length(username),
+        base_token_count,
+    );
+    match category {
+        Some(pricing) => fee
+            .map(|fee| fee * pricing.fee_multiplier)
+            .filter(|fee| !fee.is_zero()),
+        None => fee,
+    }
+}
+
+// the fees for minting a path under parent_token_id
+// returns the protocol fee set by the admin
+// and the namespace fee set by the owner of the base token
+pub fn get_path_mint_fees(
+    storage: &dyn Storage,
+    minting_fees: &MintingFeesResponse,
+    parent_token_id: &str,
+) -> StdResult<(Option<Uint128>, Option<Uint128>)> {
+    let protocol_fee = minting_fees.path_mint_fee.filter(|fee| !fee.is_zero());
+    let namespace_fee = NAMESPACE_FEES.may_load(storage, get_root_of_path(parent_token_id))?;
+    Ok((protocol_fee, namespace_fee))
+}
+
 pub fn get_number_of_owned_tokens(
     contract: &Cw721MetadataContract,
     deps: &DepsMut,
@@ -192,6 +698,37 @@ pub fn get_number_of_owned_tokens(
     Ok(number_of_tokens_owned)
 }
 
+// splits a fee between the admin and burning
+pub fn get_fee_msgs(
+    admin_address: Addr,
+    native_denom: String,
+    fee: Uint128,
+    burn_percentage: Option<u64>,
+) -> Vec<CosmosMsg> {
+    match burn_percentage {
+        Some(bp) => {
+            let fee_to_admin = fee * Decimal::percent(100 - bp);
+            let fee_to_burn = fee * Decimal::percent(bp);
+            vec![
+                BankMsg::Send {
+                    to_address: admin_address.to_string(),
+                    amount: coins(fee_to_admin.u128(), native_denom.clone()),
+                }
+                .into(),
+                BankMsg::Burn {
+                    amount: coins(fee_to_burn.u128(), native_denom),
+                }
+                .into(),
+            ]
+        }
+        None => vec![BankMsg::Send {
+            to_address: admin_address.to_string(),
+            amount: coins(fee.u128(), native_denom),
+        }
+        .into()],
+    }
+}
+
 pub fn get_mint_response(
     admin_address: Addr,
     mint_message_sender: Addr,
@@ -202,28 +739,7 @@ pub fn get_mint_response(
 ) -> Response {
     match fee {
         Some(fee) => {
-            let msgs: Vec<CosmosMsg> = match burn_percentage {
-                Some(bp) => {
-                    let fee_to_admin = fee * Decimal::percent(100 - bp);
-                    let fee_to_burn = fee * Decimal::percent(bp);
-                    vec![
-                        BankMsg::Send {
-                            to_address: admin_address.to_string(),
-                            amount: coins(fee_to_admin.u128(), native_denom.clone()),
-                        }
-                        .into(),
-                        BankMsg::Burn {
-                            amount: coins(fee_to_burn.u128(), native_denom),
-                        }
-                        .into(),
-                    ]
-                }
-                None => vec![BankMsg::Send {
-                    to_address: admin_address.to_string(),
-                    amount: coins(fee.u128(), native_denom),
-                }
-                .into()],
-            };
+            let msgs = get_fee_msgs(admin_address, native_denom, fee, burn_percentage);
 
             Response::new()
                 .add_attribute("action", "mint")
@@ -237,81 +753,3 @@ pub fn get_mint_response(
             .add_attribute("token_id", token_id),
     }
 }
-
-// -- logo helpers as they're not public in CW20 --
-const LOGO_SIZE_CAP: usize = 10 * 1024;
-
-/// Checks if data starts with XML preamble
-fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
-    // The easiest way to perform this check would be just match on regex, however regex
-    // compilation is heavy and probably not worth it.
-
-    let preamble = data.split_inclusive(|c| *c == b'>').next().ok_or_else(|| {
-        ContractError::Std(StdError::ParseErr {
-            msg: "Failed to parse SVG".to_string(),
-            target_type: "Logo".to_string(),
-        })
-    })?;
-
-    const PREFIX: &[u8] = b"<?xml ";
-    const POSTFIX: &[u8] = b"?>";
-
-    if !(preamble.starts_with(PREFIX) && preamble.ends_with(POSTFIX)) {
-        Err(ContractError::Std(StdError::ParseErr {
-            msg: "Failed to parse SVG".to_string(),
-            target_type: "Logo".to_string(),
-        }))
-    } else {
-        Ok(())
-    }
-
-    // Additionally attributes format could be validated as they are well defined, as well as
-    // comments presence inside of preable, but it is probably not worth it.
-}
-
-/// Validates XML logo
-fn verify_xml_logo(logo: &[u8]) -> Result<(), ContractError> {
-    verify_xml_preamble(logo)?;
-
-    if logo.len() > LOGO_SIZE_CAP {
-        Err(ContractError::Std(StdError::ParseErr {
-            msg: "Failed to parse SVG - too large".to_string(),
-            target_type: "Logo".to_string(),
-        }))
-    } else {
-        Ok(())
-    }
-}
-
-/// Validates png logo
-fn verify_png_logo(logo: &[u8]) -> Result<(), ContractError> {
-    // PNG header format:
-    // 0x89 - magic byte, out of ASCII table to fail on 7-bit systems
-    // "PNG" ascii representation
-    // [0x0d, 0x0a] - dos style line ending
-    // 0x1a - dos control character, stop displaying rest of the file
-    // 0x0a - unix style line ending
-    const HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
-    if logo.len() > LOGO_SIZE_CAP {
-        Err(ContractError::Std(StdError::ParseErr {
-            msg: "Failed to parse PNG - too large".to_string(),
-            target_type: "Logo".to_string(),
-        }))
-    } else if !logo.starts_with(&HEADER) {
-        Err(ContractError::Std(StdError::ParseErr {
-            msg: "Failed to parse PNG".to_string(),
-            target_type: "Logo".to_string(),
-        }))
-    } else {
-        Ok(())
-    }
-}
-
-/// Checks if passed logo is correct, and if not, returns an error
-pub fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
-    match logo {
-        Logo::Embedded(EmbeddedLogo::Svg(logo)) => verify_xml_logo(logo),
-        Logo::Embedded(EmbeddedLogo::Png(logo)) => verify_png_logo(logo),
-        Logo::Url(_) => Err(ContractError::NoLinksPermitted {}), // this is an embedded field, we don't allow URLs like CW20
-    }
-}
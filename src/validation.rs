@@ -0,0 +1,311 @@
+use crate::bech32;
+use crate::error::ContractError;
+use crate::msg::Metadata;
+use cosmwasm_std::StdError;
+use cw20::{EmbeddedLogo, Logo};
+
+// every write of metadata goes through validate_metadata
+// so what is stored is always something clients can trust
+// limits are in bytes
+pub const MAX_URL_LENGTH: usize = 512;
+pub const MAX_EMAIL_LENGTH: usize = 254;
+pub const MAX_PUBLIC_NAME_LENGTH: usize = 64;
+pub const MAX_PUBLIC_BIO_LENGTH: usize = 512;
+pub const MAX_ADDRESS_LENGTH: usize = 128;
+pub const MAX_PGP_PUBLIC_KEY_LENGTH: usize = 8 * 1024;
+
+// handles may be given with or without a leading '@'
+const MAX_TWITTER_HANDLE_LENGTH: usize = 15;
+const MIN_TELEGRAM_HANDLE_LENGTH: usize = 5;
+const MAX_TELEGRAM_HANDLE_LENGTH: usize = 32;
+const MIN_DISCORD_HANDLE_LENGTH: usize = 2;
+const MAX_DISCORD_HANDLE_LENGTH: usize = 32;
+const MIN_KEYBASE_HANDLE_LENGTH: usize = 2;
+const MAX_KEYBASE_HANDLE_LENGTH: usize = 16;
+
+// image and external_url must point somewhere
+// a browser or gateway can load safely
+const ALLOWED_URL_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+
+pub fn validate_metadata(metadata: &Metadata) -> Result<(), ContractError> {
+    if let Some(ref image) = metadata.image {
+        if image.len() > MAX_URL_LENGTH {
+            return Err(ContractError::ImageUrlTooLong {});
+        }
+        if !url_is_valid(image) {
+            return Err(ContractError::InvalidImageUrlScheme {});
+        }
+    }
+
+    // validate any embedded logo
+    if let Some(ref pfp_data) = metadata.image_data {
+        verify_logo(pfp_data)?
+    }
+
+    if let Some(ref email) = metadata.email {
+        if email.len() > MAX_EMAIL_LENGTH {
+            return Err(ContractError::EmailTooLong {});
+        }
+        if !email_is_valid(email) {
+            return Err(ContractError::InvalidEmail {});
+        }
+    }
+
+    if let Some(ref external_url) = metadata.external_url {
+        if external_url.len() > MAX_URL_LENGTH {
+            return Err(ContractError::ExternalUrlTooLong {});
+        }
+        if !url_is_valid(external_url) {
+            return Err(ContractError::InvalidExternalUrlScheme {});
+        }
+    }
+
+    if let Some(ref public_name) = metadata.public_name {
+        if public_name.len() > MAX_PUBLIC_NAME_LENGTH {
+            return Err(ContractError::PublicNameTooLong {});
+        }
+    }
+
+    if let Some(ref public_bio) = metadata.public_bio {
+        if public_bio.len() > MAX_PUBLIC_BIO_LENGTH {
+            return Err(ContractError::BioTooLong {});
+        }
+    }
+
+    if let Some(ref twitter_id) = metadata.twitter_id {
+        if !twitter_handle_is_valid(twitter_id) {
+            return Err(ContractError::InvalidTwitterHandle {});
+        }
+    }
+
+    if let Some(ref discord_id) = metadata.discord_id {
+        if !discord_handle_is_valid(discord_id) {
+            return Err(ContractError::InvalidDiscordHandle {});
+        }
+    }
+
+    if let Some(ref telegram_id) = metadata.telegram_id {
+        if !telegram_handle_is_valid(telegram_id) {
+            return Err(ContractError::InvalidTelegramHandle {});
+        }
+    }
+
+    if let Some(ref keybase_id) = metadata.keybase_id {
+        if !keybase_handle_is_valid(keybase_id) {
+            return Err(ContractError::InvalidKeybaseHandle {});
+        }
+    }
+
+    if let Some(ref validator_address) = metadata.validator_operator_address {
+        if validator_address.len() > MAX_ADDRESS_LENGTH {
+            return Err(ContractError::ValidatorOperatorAddressTooLong {});
+        }
+        if bech32::decode(validator_address).is_none() {
+            return Err(ContractError::InvalidValidatorOperatorAddress {});
+        }
+    }
+
+    if let Some(ref contract_address) = metadata.contract_address {
+        if contract_address.len() > MAX_ADDRESS_LENGTH {
+            return Err(ContractError::ContractAddressTooLong {});
+        }
+        if bech32::decode(contract_address).is_none() {
+            return Err(ContractError::InvalidContractAddress {});
+        }
+    }
+
+    // validate PGP public key format
+    if let Some(ref pgp_public_key) = metadata.pgp_public_key {
+        if pgp_public_key.len() > MAX_PGP_PUBLIC_KEY_LENGTH {
+            return Err(ContractError::PgpPublicKeyTooLong {});
+        }
+        if !pgp_pubkey_format_is_valid(pgp_public_key) {
+            return Err(ContractError::InvalidPgpPublicKey {});
+        }
+    }
+
+    Ok(())
+}
+
+// an allowed scheme followed by something, with no whitespace
+pub fn url_is_valid(url: &str) -> bool {
+    let rest = ALLOWED_URL_SCHEMES
+        .iter()
+        .find_map(|scheme| url.strip_prefix(scheme));
+    match rest {
+        Some(rest) => !rest.is_empty() && !rest.chars().any(|c| c.is_whitespace()),
+        None => false,
+    }
+}
+
+// dumb, granted
+// but a basic sense check
+// that clients don't submit something random
+// obviously if the key is the correct format
+// but still in some way incorrect it will error later
+pub fn pgp_pubkey_format_is_valid(pgp_pubkey: &str) -> bool {
+    let first_check_passed = str::starts_with(pgp_pubkey, "-----BEGIN PGP PUBLIC KEY BLOCK-----");
+    let second_check_passed = str::ends_with(pgp_pubkey, "-----END PGP PUBLIC KEY BLOCK-----");
+
+    first_check_passed && second_check_passed
+}
+
+// a pragmatic subset of RFC 5322
+// local@domain, where the domain has at least two labels
+// and ends in an alphabetic tld
+pub fn email_is_valid(email: &str) -> bool {
+    let (local, domain) = match email.rsplit_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let local_is_valid = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~.-".contains(c));
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_is_valid = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .map(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+            .unwrap_or(false);
+
+    local_is_valid && domain_is_valid
+}
+
+// 1 to 15 letters, numbers or underscores
+pub fn twitter_handle_is_valid(handle: &str) -> bool {
+    let handle = handle.strip_prefix('@').unwrap_or(handle);
+    !handle.is_empty()
+        && handle.len() <= MAX_TWITTER_HANDLE_LENGTH
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// either a current username, 2 to 32 lowercase letters, numbers, '_' or '.'
+// or a legacy name#1234 tag
+pub fn discord_handle_is_valid(handle: &str) -> bool {
+    if let Some((name, discriminator)) = handle.rsplit_once('#') {
+        let name_length = name.chars().count();
+        return (MIN_DISCORD_HANDLE_LENGTH..=MAX_DISCORD_HANDLE_LENGTH).contains(&name_length)
+            && !name.trim().is_empty()
+            && !name.contains(['@', '#', ':'])
+            && discriminator.len() == 4
+            && discriminator.chars().all(|c| c.is_ascii_digit());
+    }
+
+    (MIN_DISCORD_HANDLE_LENGTH..=MAX_DISCORD_HANDLE_LENGTH).contains(&handle.len())
+        && !handle.contains("..")
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+}
+
+// 5 to 32 letters, numbers or underscores, starting with a letter
+pub fn telegram_handle_is_valid(handle: &str) -> bool {
+    let handle = handle.strip_prefix('@').unwrap_or(handle);
+    (MIN_TELEGRAM_HANDLE_LENGTH..=MAX_TELEGRAM_HANDLE_LENGTH).contains(&handle.len())
+        && handle.starts_with(|c: char| c.is_ascii_alphabetic())
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// 2 to 16 lowercase letters, numbers or underscores
+pub fn keybase_handle_is_valid(handle: &str) -> bool {
+    (MIN_KEYBASE_HANDLE_LENGTH..=MAX_KEYBASE_HANDLE_LENGTH).contains(&handle.len())
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+// -- logo helpers as they're not public in CW20 --
+const LOGO_SIZE_CAP: usize = 10 * 1024;
+
+/// Checks if data starts with XML preamble
+fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
+    // The easiest way to perform this check would be just match on regex, however regex
+    // compilation is heavy and probably not worth it.
+
+    let preamble = data.split_inclusive(|c| *c == b'>').next().ok_or_else(|| {
+        ContractError::Std(StdError::ParseErr {
+            msg: "Failed to parse SVG".to_string(),
+            target_type: "Logo".to_string(),
+        })
+    })?;
+
+    const PREFIX: &[u8] = b"<?xml ";
+    const POSTFIX: &[u8] = b"?>";
+
+    if !(preamble.starts_with(PREFIX) && preamble.ends_with(POSTFIX)) {
+        Err(ContractError::Std(StdError::ParseErr {
+            msg: "Failed to parse SVG".to_string(),
+            target_type: "Logo".to_string(),
+        }))
+    } else {
+        Ok(())
+    }
+
+    // Additionally attributes format could be validated as they are well defined, as well as
+    // comments presence inside of preable, but it is probably not worth it.
+}
+
+/// Validates XML logo
+fn verify_xml_logo(logo: &[u8]) -> Result<(), ContractError> {
+    verify_xml_preamble(logo)?;
+
+    if logo.len() > LOGO_SIZE_CAP {
+        Err(ContractError::Std(StdError::ParseErr {
+            msg: "Failed to parse SVG - too large".to_string(),
+            target_type: "Logo".to_string(),
+        }))
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates png logo
+fn verify_png_logo(logo: &[u8]) -> Result<(), ContractError> {
+    // PNG header format:
+    // 0x89 - magic byte, out of ASCII table to fail on 7-bit systems
+    // "PNG" ascii representation
+    // [0x0d, 0x0a] - dos style line ending
+    // 0x1a - dos control character, stop displaying rest of the file
+    // 0x0a - unix style line ending
+    const HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    if logo.len() > LOGO_SIZE_CAP {
+        Err(ContractError::Std(StdError::ParseErr {
+            msg: "Failed to parse PNG - too large".to_string(),
+            target_type: "Logo".to_string(),
+        }))
+    } else if !logo.starts_with(&HEADER) {
+        Err(ContractError::Std(StdError::ParseErr {
+            msg: "Failed to parse PNG".to_string(),
+            target_type: "Logo".to_string(),
+        }))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks if passed logo is correct, and if not, returns an error
+pub fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+    match logo {
+        Logo::Embedded(EmbeddedLogo::Svg(logo)) => verify_xml_logo(logo),
+        Logo::Embedded(EmbeddedLogo::Png(logo)) => verify_png_logo(logo),
+        Logo::Url(_) => Err(ContractError::NoLinksPermitted {}), // this is an embedded field, we don't allow URLs like CW20
+    }
+}